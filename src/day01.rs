@@ -1,58 +1,440 @@
-use std::collections::HashMap;
+mod utils;
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt::{Debug, Display};
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::hash::Hash;
+use std::io::{BufRead, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use utils::cli::flag_value;
 
 const INPUT_FILE: &str = "input/day01.txt";
+const STREAM_CHUNK_SIZE: usize = 1 << 20;
+const MAX_MODES: usize = 5;
+const MAX_FAN_IN: usize = 64;
 
 fn main() {
     println!("Day 1");
 
-    // Read lists from file
-    let (list1, list2) = read_input(INPUT_FILE).expect("Failed to read input file");
+    let args: Vec<String> = std::env::args().collect();
+    let metrics = parse_metrics(&args).expect("Invalid metric options");
+    let (first, second) = parse_columns(&args).expect("Invalid column options");
+    let explain_format = parse_explain(&args).expect("Invalid explain options");
+
+    // IDs are unsigned unless `--signed` is given
+    if args.iter().any(|arg| arg == "--signed") {
+        run::<i64>(&args, first, second, &metrics, explain_format);
+    } else {
+        run::<u64>(&args, first, second, &metrics, explain_format);
+    }
+}
+
+// Solves both parts with the lists in columns `first` and `second` of the input file.
+fn run<T: Id>(
+    args: &[String],
+    first: usize,
+    second: usize,
+    metrics: &Metrics,
+    explain_format: Option<ExplainFormat>,
+) {
+    // Lists larger than memory are sorted in chunks on disk
+    if args.iter().any(|arg| arg == "--stream") {
+        let (dist, similarity) =
+            stream_totals::<T>(INPUT_FILE, first, second, STREAM_CHUNK_SIZE, metrics)
+                .expect("Failed to read input file");
+        println!("[Part 1] {}", dist);
+        println!("[Part 2] {}", similarity);
+        return;
+    }
 
-    assert_eq!(list1.len(), list2.len(), "Lists must have the same length");
+    // Read lists from file
+    let lists: Vec<Vec<T>> = read_input(INPUT_FILE).expect("Failed to read input file");
+    let (list1, list2) = column_pair(&lists, first, second).expect("Invalid columns");
 
     // Part 1: Total distance
-    let dist = calculate_total_distance(&list1, &list2);
+    let dist = calculate_total_distance(list1, list2, metrics.distance.as_ref())
+        .expect("Failed to calculate total distance");
     println!("[Part 1] {}", dist);
 
     // Part 2: Similarity index
-    let similarity = calculate_similarity_index(&list1, &list2);
+    let similarity = calculate_similarity_index(list1, list2, metrics.similarity.as_ref())
+        .expect("Failed to calculate similarity index");
     println!("[Part 2] {}", similarity);
+
+    // Statistics of every list, with `--stats`
+    if args.iter().any(|arg| arg == "--stats") {
+        for (k, list) in lists.iter().enumerate() {
+            println!("List {}: {}", k + 1, list_stats(list).summary());
+        }
+    }
+
+    // Every term of both answers
+    if let Some(format) = explain_format {
+        let explanation = explain(list1, list2, metrics).expect("Failed to explain answers");
+        match format {
+            ExplainFormat::Table => println!("{}", explanation.table()),
+            ExplainFormat::Json => println!("{}", explanation.json()),
+        }
+    }
+}
+
+/// An integer type that can be used as a location ID.
+trait Id: Copy + Ord + Hash + Send + Sync + Debug + Display + FromStr {
+    /// The ID widened so that metrics can combine it with counts and ranks.
+    fn value(self) -> i128;
+
+    /// The ID widened to 64 bits, as stored in the run files of the streaming mode.
+    fn to_bytes(self) -> [u8; 8];
+
+    fn from_bytes(bytes: [u8; 8]) -> Self;
+}
+
+macro_rules! impl_id {
+    ($($t:ty => $wide:ty),*) => {
+        $(
+            impl Id for $t {
+                fn value(self) -> i128 {
+                    self as i128
+                }
+
+                fn to_bytes(self) -> [u8; 8] {
+                    (self as $wide).to_le_bytes()
+                }
+
+                fn from_bytes(bytes: [u8; 8]) -> Self {
+                    <$wide>::from_le_bytes(bytes) as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_id!(u32 => u64, u64 => u64, i32 => i64, i64 => i64);
+
+/// Two lists compared against each other.
+type ListPair<'a, T> = (&'a [T], &'a [T]);
+
+/// How far apart two paired IDs are.
+trait DistanceMetric: Sync {
+    /// Distance between the IDs `a` and `b` found at `rank` in both sorted lists, or None if it
+    /// does not fit in u64.
+    fn distance(&self, rank: usize, a: i128, b: i128) -> Option<u64>;
+}
+
+/// Absolute difference, as defined by the puzzle.
+struct AbsoluteDistance;
+
+impl DistanceMetric for AbsoluteDistance {
+    fn distance(&self, _rank: usize, a: i128, b: i128) -> Option<u64> {
+        a.abs_diff(b).try_into().ok()
+    }
+}
+
+/// Square of the absolute difference.
+struct SquaredDistance;
+
+impl DistanceMetric for SquaredDistance {
+    fn distance(&self, _rank: usize, a: i128, b: i128) -> Option<u64> {
+        let diff: u64 = a.abs_diff(b).try_into().ok()?;
+        diff.checked_mul(diff)
+    }
+}
+
+/// Absolute difference multiplied by the 1-based rank of the pair, so that pairs of larger IDs
+/// weigh more.
+struct RankWeightedDistance;
+
+impl DistanceMetric for RankWeightedDistance {
+    fn distance(&self, rank: usize, a: i128, b: i128) -> Option<u64> {
+        let diff: u64 = a.abs_diff(b).try_into().ok()?;
+        diff.checked_mul(rank as u64 + 1)
+    }
+}
+
+/// How much an ID found in both lists adds to the similarity index.
+trait SimilarityMetric: Sync {
+    /// Contribution of `id`, found `count1` times in the first list and `count2` times in the
+    /// second, or None if it overflows.
+    fn similarity(&self, id: i128, count1: usize, count2: usize) -> Option<i128>;
+}
+
+/// ID multiplied by both counts, as defined by the puzzle.
+struct CountSimilarity;
+
+impl SimilarityMetric for CountSimilarity {
+    fn similarity(&self, id: i128, count1: usize, count2: usize) -> Option<i128> {
+        id.checked_mul(count1 as i128)?.checked_mul(count2 as i128)
+    }
+}
+
+/// Count similarity multiplied by a weight per ID.
+struct WeightedSimilarity {
+    weights: HashMap<i128, i128>,
+    /// Weight of the IDs missing from `weights`.
+    default: i128,
+}
+
+impl WeightedSimilarity {
+    // Reads the weights from a file with an ID and its weight on each line.
+    fn read(file_path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let lists: Vec<Vec<i64>> = read_input(file_path)?;
+        let (ids, weights) = column_pair(&lists, 0, 1)?;
+
+        Ok(Self {
+            weights: ids
+                .iter()
+                .zip(weights)
+                .map(|(id, weight)| (id.value(), weight.value()))
+                .collect(),
+            default: 1,
+        })
+    }
+}
+
+impl SimilarityMetric for WeightedSimilarity {
+    fn similarity(&self, id: i128, count1: usize, count2: usize) -> Option<i128> {
+        let weight = self.weights.get(&id).copied().unwrap_or(self.default);
+        CountSimilarity
+            .similarity(id, count1, count2)?
+            .checked_mul(weight)
+    }
+}
+
+/// The metrics used by both parts.
+struct Metrics {
+    distance: Box<dyn DistanceMetric>,
+    similarity: Box<dyn SimilarityMetric>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            distance: Box::new(AbsoluteDistance),
+            similarity: Box::new(CountSimilarity),
+        }
+    }
+}
+
+// Selects the metrics with the `--distance <absolute|squared|rank-weighted>` and
+// `--weights <file>` command line flags.
+fn parse_metrics(args: &[String]) -> Result<Metrics, Box<dyn std::error::Error>> {
+    let mut metrics = Metrics::default();
+
+    if let Some(name) = flag_value(args, "--distance") {
+        metrics.distance = match name {
+            "absolute" => Box::new(AbsoluteDistance),
+            "squared" => Box::new(SquaredDistance),
+            "rank-weighted" => Box::new(RankWeightedDistance),
+            _ => return Err(format!("Unknown distance metric: {}", name).into()),
+        };
+    }
+
+    if let Some(file_path) = flag_value(args, "--weights") {
+        metrics.similarity = Box::new(WeightedSimilarity::read(file_path)?);
+    }
+
+    Ok(metrics)
+}
+
+// Selects the two compared lists with the `--columns <a>,<b>` command line flag, numbered from 1.
+// Defaults to the first two columns.
+fn parse_columns(args: &[String]) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    let Some(value) = flag_value(args, "--columns") else {
+        return Ok((0, 1));
+    };
+
+    let column = |field: &str| match field.trim().parse::<usize>() {
+        Ok(column) if column > 0 => Ok(column - 1),
+        _ => Err(format!("Invalid column: '{}'", field)),
+    };
+
+    match value.split_once(',') {
+        Some((first, second)) => Ok((column(first)?, column(second)?)),
+        None => Err(format!("Expected two columns as <a>,<b>, found '{}'", value).into()),
+    }
+}
+
+// Selects how to print the terms of both answers with the `--explain <table|json>` command line
+// flag. The streaming mode keeps no terms, so it cannot explain them.
+fn parse_explain(args: &[String]) -> Result<Option<ExplainFormat>, Box<dyn std::error::Error>> {
+    if !args.iter().any(|arg| arg == "--explain") {
+        return Ok(None);
+    }
+
+    if args.iter().any(|arg| arg == "--stream") {
+        return Err("--explain cannot be combined with --stream".into());
+    }
+
+    match flag_value(args, "--explain") {
+        Some("table") => Ok(Some(ExplainFormat::Table)),
+        Some("json") => Ok(Some(ExplainFormat::Json)),
+        Some(format) => Err(format!("Unknown explain format: {}", format).into()),
+        None => Err("--explain needs a format, table or json".into()),
+    }
+}
+
+/// Summary statistics of a single list.
+#[derive(Debug, Clone, PartialEq)]
+struct ListStats<T> {
+    /// Middle value, or the mean of the two middle values. None for an empty list.
+    median: Option<f64>,
+    /// Most frequent IDs, in increasing order.
+    modes: Vec<T>,
+    /// Number of times each mode appears.
+    mode_count: usize,
+    /// IDs that appear more than once with their counts, in increasing order of ID.
+    duplicates: Vec<(T, usize)>,
+}
+
+impl<T: Id> ListStats<T> {
+    // Describes the statistics in one line. Modes are only listed when some ID repeats, and at
+    // most MAX_MODES of them.
+    fn summary(&self) -> String {
+        let median = self
+            .median
+            .map_or("-".to_string(), |median| median.to_string());
+
+        let mode = if self.mode_count > 1 {
+            let mut modes: Vec<String> = self
+                .modes
+                .iter()
+                .take(MAX_MODES)
+                .map(|id| id.to_string())
+                .collect();
+            if self.modes.len() > MAX_MODES {
+                modes.push(format!("{} more", self.modes.len() - MAX_MODES));
+            }
+            format!("[{}] ({} times)", modes.join(", "), self.mode_count)
+        } else {
+            "none".to_string()
+        };
+
+        format!(
+            "median {}, mode {}, {} duplicated IDs",
+            median,
+            mode,
+            self.duplicates.len()
+        )
+    }
 }
 
-fn read_input(file_path: &str) -> Result<(Vec<u32>, Vec<u32>), Box<dyn std::error::Error>> {
+fn read_input<T: Id>(file_path: &str) -> Result<Vec<Vec<T>>, Box<dyn std::error::Error>> {
     let file = File::open(file_path)?;
-    let reader = BufReader::new(file);
+    parse_lists(BufReader::new(file))
+}
+
+// Reads one list per whitespace-separated column.
+fn parse_lists<T: Id, R: BufRead>(reader: R) -> Result<Vec<Vec<T>>, Box<dyn std::error::Error>> {
+    let mut lists: Vec<Vec<T>> = Vec::new();
 
-    let mut list1 = Vec::with_capacity(1000); // Allocate capacity for efficiency
-    let mut list2 = Vec::with_capacity(1000);
+    for ids in parse_rows(reader) {
+        let ids = ids?;
+        lists.resize_with(ids.len(), Vec::new);
+        for (list, id) in lists.iter_mut().zip(ids) {
+            list.push(id);
+        }
+    }
 
-    for line in reader.lines() {
+    Ok(lists)
+}
+
+// Iterates over the IDs of every line. Every line must have as many IDs as the first one.
+fn parse_rows<T: Id, R: BufRead>(
+    reader: R,
+) -> impl Iterator<Item = Result<Vec<T>, Box<dyn std::error::Error>>> {
+    let mut columns = None;
+
+    reader.lines().enumerate().map(move |(row, line)| {
         let line = line?;
-        let mut parts = line.split_whitespace();
+        let fields: Vec<&str> = line.split_whitespace().collect();
+
+        let expected = *columns.get_or_insert(fields.len());
+        if expected == 0 {
+            return Err("Line 1: no IDs found".into());
+        }
 
-        let first: u32 = parts
-            .next()
-            .ok_or("Missing first ID")?
-            .parse()
-            .map_err(|_| "Cannot convert first ID to u32")?;
-        let second: u32 = parts
-            .next()
-            .ok_or("Missing second ID")?
-            .parse()
-            .map_err(|_| "Cannot convert second ID to u32")?;
+        if fields.len() != expected {
+            return Err(format!(
+                "Line {}: expected {} IDs, found {}",
+                row + 1,
+                expected,
+                fields.len()
+            )
+            .into());
+        }
 
-        list1.push(first);
-        list2.push(second);
+        fields
+            .iter()
+            .enumerate()
+            .map(|(column, field)| {
+                field.parse().map_err(|_| {
+                    format!(
+                        "Line {}, column {}: cannot convert '{}' to an ID",
+                        row + 1,
+                        column + 1,
+                        field
+                    )
+                    .into()
+                })
+            })
+            .collect()
+    })
+}
+
+// Selects two of the lists by column index. An input without lines has every column empty.
+fn column_pair<T>(
+    lists: &[Vec<T>],
+    first: usize,
+    second: usize,
+) -> Result<ListPair<'_, T>, Box<dyn std::error::Error>> {
+    if lists.is_empty() {
+        return Ok((&[], &[]));
     }
 
-    Ok((list1, list2))
+    let get = |column: usize| {
+        lists
+            .get(column)
+            .map(|list| list.as_slice())
+            .ok_or_else(|| {
+                format!(
+                    "Column {} does not exist, there are {}",
+                    column + 1,
+                    lists.len()
+                )
+            })
+    };
+
+    Ok((get(first)?, get(second)?))
+}
+
+fn calculate_total_distance<T: Id>(
+    list1: &[T],
+    list2: &[T],
+    metric: &dyn DistanceMetric,
+) -> Result<u64, Box<dyn std::error::Error>> {
+    sum_distances(&distance_terms(list1, list2, metric)?)
+}
+
+fn calculate_similarity_index<T: Id>(
+    list1: &[T],
+    list2: &[T],
+    metric: &dyn SimilarityMetric,
+) -> Result<i128, Box<dyn std::error::Error>> {
+    sum_similarities(&similarity_terms(list1, list2, metric)?)
 }
 
-fn calculate_total_distance(list1: &[u32], list2: &[u32]) -> u32 {
+// Pairs the sorted lists rank by rank.
+fn distance_terms<T: Id>(
+    list1: &[T],
+    list2: &[T],
+    metric: &dyn DistanceMetric,
+) -> Result<Vec<DistanceTerm>, Box<dyn std::error::Error>> {
     let mut sorted1 = list1.to_vec();
     let mut sorted2 = list2.to_vec();
 
@@ -62,27 +444,686 @@ fn calculate_total_distance(list1: &[u32], list2: &[u32]) -> u32 {
     sorted1
         .par_iter()
         .zip(sorted2.par_iter())
-        .map(|(a, b)| a.abs_diff(*b))
-        .sum()
+        .enumerate()
+        .map(|(rank, (a, b))| {
+            let (left, right) = (a.value(), b.value());
+            Some(DistanceTerm {
+                rank,
+                left,
+                right,
+                distance: metric.distance(rank, left, right)?,
+            })
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| "Distance overflows u64".into())
 }
 
-fn calculate_similarity_index(list1: &[u32], list2: &[u32]) -> u32 {
+// Counts every ID of the first list in both lists, in increasing order of ID.
+fn similarity_terms<T: Id>(
+    list1: &[T],
+    list2: &[T],
+    metric: &dyn SimilarityMetric,
+) -> Result<Vec<SimilarityTerm>, Box<dyn std::error::Error>> {
     let count1 = count_elements(list1);
     let count2 = count_elements(list2);
+    let mut ids: Vec<&T> = count1.keys().collect();
+    ids.sort_unstable();
 
-    count1
-        .par_iter()
-        .map(|(&id, &count1)| {
-            let count2 = count2.get(&id).copied().unwrap_or(0);
-            id * (count1 * count2) as u32
+    ids.par_iter()
+        .map(|&id| {
+            let (left_count, right_count) = (count1[id], count2.get(id).copied().unwrap_or(0));
+            let contribution = match right_count {
+                0 => 0,
+                _ => metric.similarity(id.value(), left_count, right_count)?,
+            };
+            Some(SimilarityTerm {
+                id: id.value(),
+                left_count,
+                right_count,
+                contribution,
+            })
         })
-        .sum()
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| "Similarity overflows i128".into())
 }
 
-fn count_elements(list: &[u32]) -> HashMap<u32, usize> {
+fn sum_distances(terms: &[DistanceTerm]) -> Result<u64, Box<dyn std::error::Error>> {
+    terms
+        .par_iter()
+        .map(|term| Some(term.distance))
+        .try_reduce(|| 0, |x, y| x.checked_add(y))
+        .ok_or_else(|| "Total distance overflows u64".into())
+}
+
+fn sum_similarities(terms: &[SimilarityTerm]) -> Result<i128, Box<dyn std::error::Error>> {
+    terms
+        .par_iter()
+        .map(|term| Some(term.contribution))
+        .try_reduce(|| 0, |x, y| x.checked_add(y))
+        .ok_or_else(|| "Similarity index overflows i128".into())
+}
+
+fn count_elements<T: Id>(list: &[T]) -> HashMap<T, usize> {
     let mut counts = HashMap::new();
     for &id in list {
         *counts.entry(id).or_insert(0) += 1;
     }
     counts
 }
+
+fn list_stats<T: Id>(list: &[T]) -> ListStats<T> {
+    let mut sorted = list.to_vec();
+    sorted.sort_unstable();
+
+    let middle = sorted.len() / 2;
+    let median = match sorted.len() {
+        0 => None,
+        len if len % 2 == 1 => Some(sorted[middle].value() as f64),
+        _ => Some((sorted[middle - 1].value() + sorted[middle].value()) as f64 / 2.0),
+    };
+
+    // Runs of equal IDs in the sorted list
+    let mut runs: Vec<(T, usize)> = Vec::new();
+    for id in sorted {
+        match runs.last_mut() {
+            Some((last, count)) if *last == id => *count += 1,
+            _ => runs.push((id, 1)),
+        }
+    }
+
+    let mode_count = runs.iter().map(|&(_, count)| count).max().unwrap_or(0);
+    let modes = runs
+        .iter()
+        .filter(|&&(_, count)| count == mode_count)
+        .map(|&(id, _)| id)
+        .collect();
+    let duplicates = runs.into_iter().filter(|&(_, count)| count > 1).collect();
+
+    ListStats {
+        median,
+        modes,
+        mode_count,
+        duplicates,
+    }
+}
+
+/// One pair of the total distance.
+#[derive(Debug, Clone, PartialEq)]
+struct DistanceTerm {
+    rank: usize,
+    left: i128,
+    right: i128,
+    distance: u64,
+}
+
+/// One ID of the similarity index.
+#[derive(Debug, Clone, PartialEq)]
+struct SimilarityTerm {
+    id: i128,
+    left_count: usize,
+    right_count: usize,
+    contribution: i128,
+}
+
+/// How `--explain` prints the terms of both answers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ExplainFormat {
+    Table,
+    Json,
+}
+
+/// How both answers are built, term by term.
+#[derive(Debug, Clone, PartialEq)]
+struct Explanation {
+    /// Pairs of the sorted lists, by rank.
+    distances: Vec<DistanceTerm>,
+    total_distance: u64,
+    /// IDs of the first list, in increasing order.
+    similarities: Vec<SimilarityTerm>,
+    similarity_index: i128,
+}
+
+impl Explanation {
+    // Lists the terms as two aligned text tables.
+    fn table(&self) -> String {
+        let mut output = String::new();
+
+        output.push_str(&format!(
+            "{:>8} {:>20} {:>20} {:>20}\n",
+            "Rank", "Left", "Right", "Distance"
+        ));
+        for term in &self.distances {
+            output.push_str(&format!(
+                "{:>8} {:>20} {:>20} {:>20}\n",
+                term.rank, term.left, term.right, term.distance
+            ));
+        }
+        output.push_str(&format!("Total distance: {}\n\n", self.total_distance));
+
+        output.push_str(&format!(
+            "{:>20} {:>12} {:>12} {:>24}\n",
+            "ID", "Left count", "Right count", "Contribution"
+        ));
+        for term in &self.similarities {
+            output.push_str(&format!(
+                "{:>20} {:>12} {:>12} {:>24}\n",
+                term.id, term.left_count, term.right_count, term.contribution
+            ));
+        }
+        output.push_str(&format!("Similarity index: {}", self.similarity_index));
+
+        output
+    }
+
+    // Serializes the terms as a JSON object.
+    fn json(&self) -> String {
+        let pairs: Vec<String> = self
+            .distances
+            .iter()
+            .map(|term| {
+                format!(
+                    "{{\"rank\": {}, \"left\": {}, \"right\": {}, \"distance\": {}}}",
+                    term.rank, term.left, term.right, term.distance
+                )
+            })
+            .collect();
+        let ids: Vec<String> = self
+            .similarities
+            .iter()
+            .map(|term| {
+                format!(
+                    "{{\"id\": {}, \"left_count\": {}, \"right_count\": {}, \"contribution\": {}}}",
+                    term.id, term.left_count, term.right_count, term.contribution
+                )
+            })
+            .collect();
+
+        format!(
+            "{{\"distance\": {{\"total\": {}, \"pairs\": [{}]}}, \"similarity\": {{\"total\": {}, \"ids\": [{}]}}}}",
+            self.total_distance,
+            pairs.join(", "),
+            self.similarity_index,
+            ids.join(", ")
+        )
+    }
+}
+
+// Computes both answers keeping every term, so that they can be audited.
+fn explain<T: Id>(
+    list1: &[T],
+    list2: &[T],
+    metrics: &Metrics,
+) -> Result<Explanation, Box<dyn std::error::Error>> {
+    let distances = distance_terms(list1, list2, metrics.distance.as_ref())?;
+    let similarities = similarity_terms(list1, list2, metrics.similarity.as_ref())?;
+
+    Ok(Explanation {
+        total_distance: sum_distances(&distances)?,
+        distances,
+        similarity_index: sum_similarities(&similarities)?,
+        similarities,
+    })
+}
+
+/// Sorted runs of IDs spilled to temporary files, deleted when dropped.
+struct SortedRuns<T> {
+    paths: Vec<PathBuf>,
+    ids: PhantomData<T>,
+}
+
+impl<T: Id> SortedRuns<T> {
+    fn new() -> Self {
+        Self {
+            paths: Vec::new(),
+            ids: PhantomData,
+        }
+    }
+
+    // Sorts `ids` and writes them to a new run file.
+    fn spill(&mut self, ids: &mut Vec<T>) -> std::io::Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        ids.sort_unstable();
+
+        let mut writer = self.create_run()?;
+        for id in ids.drain(..) {
+            writer.write_all(&id.to_bytes())?;
+        }
+        writer.flush()
+    }
+
+    // Adds an empty run file, to be written in sorted order.
+    fn create_run(&mut self) -> std::io::Result<BufWriter<File>> {
+        static RUN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let path = std::env::temp_dir().join(format!(
+            "day01-{}-{}.run",
+            std::process::id(),
+            RUN_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        self.paths.push(path.clone());
+
+        Ok(BufWriter::new(File::create(path)?))
+    }
+
+    // Merges the runs in batches of MAX_FAN_IN into longer runs, until they are few enough to
+    // be open all at once.
+    fn compact(&mut self) -> std::io::Result<()> {
+        while self.paths.len() > MAX_FAN_IN {
+            let mut merged = SortedRuns::<T>::new();
+            for batch in self.paths.chunks(MAX_FAN_IN) {
+                let mut batch = MergedRuns::<T>::open(batch)?;
+                let mut writer = merged.create_run()?;
+                while let Some(id) = batch.next_id()? {
+                    writer.write_all(&id.to_bytes())?;
+                }
+                writer.flush()?;
+            }
+
+            // The shorter runs are deleted when `merged` is dropped
+            std::mem::swap(&mut self.paths, &mut merged.paths);
+        }
+
+        Ok(())
+    }
+
+    // Reads all the runs back as a single sorted stream.
+    fn merge(&mut self) -> std::io::Result<MergedRuns<T>> {
+        self.compact()?;
+        MergedRuns::open(&self.paths)
+    }
+}
+
+impl<T> Drop for SortedRuns<T> {
+    fn drop(&mut self) {
+        for path in &self.paths {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// K-way merge of sorted runs.
+struct MergedRuns<T> {
+    readers: Vec<BufReader<File>>,
+    heap: BinaryHeap<Reverse<(T, usize)>>,
+}
+
+impl<T: Id> MergedRuns<T> {
+    fn open(paths: &[PathBuf]) -> std::io::Result<Self> {
+        let mut merged = Self {
+            readers: Vec::with_capacity(paths.len()),
+            heap: BinaryHeap::with_capacity(paths.len()),
+        };
+
+        for (run, path) in paths.iter().enumerate() {
+            let mut reader = BufReader::new(File::open(path)?);
+            if let Some(id) = read_id(&mut reader)? {
+                merged.heap.push(Reverse((id, run)));
+            }
+            merged.readers.push(reader);
+        }
+
+        Ok(merged)
+    }
+
+    // Takes the smallest ID left in any run.
+    fn next_id(&mut self) -> std::io::Result<Option<T>> {
+        let Some(Reverse((id, run))) = self.heap.pop() else {
+            return Ok(None);
+        };
+
+        if let Some(next) = read_id(&mut self.readers[run])? {
+            self.heap.push(Reverse((next, run)));
+        }
+
+        Ok(Some(id))
+    }
+
+    // Takes the smallest ID left together with the number of times it appears.
+    fn next_run(&mut self) -> std::io::Result<Option<(T, usize)>> {
+        let Some(id) = self.next_id()? else {
+            return Ok(None);
+        };
+
+        let mut count = 1;
+        while self
+            .heap
+            .peek()
+            .is_some_and(|Reverse((next, _))| *next == id)
+        {
+            self.next_id()?;
+            count += 1;
+        }
+
+        Ok(Some((id, count)))
+    }
+}
+
+fn read_id<T: Id>(reader: &mut BufReader<File>) -> std::io::Result<Option<T>> {
+    let mut bytes = [0; 8];
+    match reader.read_exact(&mut bytes) {
+        Ok(()) => Ok(Some(T::from_bytes(bytes))),
+        Err(error) if error.kind() == ErrorKind::UnexpectedEof => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+// Computes the total distance and the similarity index of two columns of a file, keeping at most
+// `chunk_size` IDs of each column in memory. Both columns are sorted externally, so the
+// similarity index is a merge join of the two sorted streams and matches the in-memory result
+// exactly.
+fn stream_totals<T: Id>(
+    file_path: &str,
+    first: usize,
+    second: usize,
+    chunk_size: usize,
+    metrics: &Metrics,
+) -> Result<(u64, i128), Box<dyn std::error::Error>> {
+    let file = File::open(file_path)?;
+    let chunk_size = chunk_size.max(1);
+
+    let mut runs1 = SortedRuns::new();
+    let mut runs2 = SortedRuns::new();
+    let mut chunk1 = Vec::with_capacity(chunk_size);
+    let mut chunk2 = Vec::with_capacity(chunk_size);
+
+    for ids in parse_rows::<T, _>(BufReader::new(file)) {
+        let ids = ids?;
+        let (Some(&id1), Some(&id2)) = (ids.get(first), ids.get(second)) else {
+            return Err(format!(
+                "Columns {} and {} do not both exist, there are {}",
+                first + 1,
+                second + 1,
+                ids.len()
+            )
+            .into());
+        };
+
+        chunk1.push(id1);
+        chunk2.push(id2);
+        if chunk1.len() == chunk_size {
+            runs1.spill(&mut chunk1)?;
+            runs2.spill(&mut chunk2)?;
+        }
+    }
+    runs1.spill(&mut chunk1)?;
+    runs2.spill(&mut chunk2)?;
+
+    // Part 1: pair the IDs in sorted order. The runs are closed before they are merged again.
+    let mut distance: u64 = 0;
+    {
+        let (mut merged1, mut merged2) = (runs1.merge()?, runs2.merge()?);
+        let mut rank = 0;
+        while let (Some(a), Some(b)) = (merged1.next_id()?, merged2.next_id()?) {
+            distance = metrics
+                .distance
+                .distance(rank, a.value(), b.value())
+                .and_then(|d| distance.checked_add(d))
+                .ok_or("Total distance overflows u64")?;
+            rank += 1;
+        }
+    }
+
+    // Part 2: join the runs of equal IDs
+    let mut similarity: i128 = 0;
+    let (mut merged1, mut merged2) = (runs1.merge()?, runs2.merge()?);
+    let (mut run1, mut run2) = (merged1.next_run()?, merged2.next_run()?);
+    while let (Some((id1, count1)), Some((id2, count2))) = (run1, run2) {
+        match id1.cmp(&id2) {
+            std::cmp::Ordering::Less => run1 = merged1.next_run()?,
+            std::cmp::Ordering::Greater => run2 = merged2.next_run()?,
+            std::cmp::Ordering::Equal => {
+                similarity = metrics
+                    .similarity
+                    .similarity(id1.value(), count1, count2)
+                    .and_then(|contribution| similarity.checked_add(contribution))
+                    .ok_or("Similarity index overflows i128")?;
+                run1 = merged1.next_run()?;
+                run2 = merged2.next_run()?;
+            }
+        }
+    }
+
+    Ok((distance, similarity))
+}
+
+#[test]
+fn test_example() {
+    const INPUT: &str = "3   4\n\
+                         4   3\n\
+                         2   5\n\
+                         1   3\n\
+                         3   9\n\
+                         3   3";
+
+    let lists: Vec<Vec<u64>> = parse_lists(INPUT.as_bytes()).unwrap();
+    let (list1, list2) = column_pair(&lists, 0, 1).unwrap();
+    assert_eq!(
+        calculate_total_distance(list1, list2, &AbsoluteDistance).unwrap(),
+        11
+    );
+    assert_eq!(
+        calculate_similarity_index(list1, list2, &CountSimilarity).unwrap(),
+        31
+    );
+
+    let stats = list_stats(list2);
+    assert_eq!(stats.median, Some(3.5));
+    assert_eq!(stats.modes, vec![3]);
+    assert_eq!(stats.mode_count, 3);
+    assert_eq!(stats.duplicates, vec![(3, 3)]);
+    assert_eq!(
+        stats.summary(),
+        "median 3.5, mode [3] (3 times), 1 duplicated IDs"
+    );
+
+    let unique: Vec<u64> = (0..1000).collect();
+    assert_eq!(
+        list_stats(&unique).summary(),
+        "median 499.5, mode none, 0 duplicated IDs"
+    );
+    let pairs: Vec<u64> = (0..1000).map(|id| id / 2).collect();
+    assert_eq!(
+        list_stats(&pairs).summary(),
+        "median 249.5, mode [0, 1, 2, 3, 4, 495 more] (2 times), 500 duplicated IDs"
+    );
+}
+
+#[test]
+fn test_columns() {
+    const INPUT: &str = "-3 4 10\n\
+                         2 -1 10\n\
+                         7 0 -5";
+
+    let lists: Vec<Vec<i64>> = parse_lists(INPUT.as_bytes()).unwrap();
+    assert_eq!(lists.len(), 3);
+
+    let (list1, list3) = column_pair(&lists, 0, 2).unwrap();
+    assert_eq!(
+        calculate_total_distance(list1, list3, &AbsoluteDistance).unwrap(),
+        2 + 8 + 3
+    );
+    assert_eq!(
+        calculate_similarity_index(&lists[2], &lists[2], &CountSimilarity).unwrap(),
+        -5 + 10 * 4
+    );
+    assert!(column_pair(&lists, 0, 3).is_err());
+
+    assert!(parse_lists::<i64, _>("1 2\n3 4 5".as_bytes()).is_err());
+    assert!(parse_lists::<i64, _>("1 2\n3".as_bytes()).is_err());
+    assert!(parse_lists::<u64, _>("1 -2".as_bytes()).is_err());
+
+    let args = |columns: &str| ["day01", "--columns", columns].map(String::from);
+    assert_eq!(parse_columns(&args("3,1")).unwrap(), (2, 0));
+    assert_eq!(parse_columns(&["day01".to_string()]).unwrap(), (0, 1));
+    assert!(parse_columns(&args("0,1")).is_err());
+    assert!(parse_columns(&args("2")).is_err());
+    assert!(parse_columns(&args("a,b")).is_err());
+}
+
+#[test]
+fn test_empty_input() {
+    let lists: Vec<Vec<u64>> = parse_lists("".as_bytes()).unwrap();
+    let (list1, list2) = column_pair(&lists, 0, 1).unwrap();
+    assert_eq!(
+        calculate_total_distance(list1, list2, &AbsoluteDistance).unwrap(),
+        0
+    );
+    assert_eq!(
+        calculate_similarity_index(list1, list2, &CountSimilarity).unwrap(),
+        0
+    );
+
+    let path = std::env::temp_dir().join(format!("day01-{}-empty.txt", std::process::id()));
+    std::fs::write(&path, "").unwrap();
+    let path = path.to_str().unwrap();
+    assert_eq!(
+        stream_totals::<u64>(path, 0, 1, 2, &Metrics::default()).unwrap(),
+        (0, 0)
+    );
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_stream() {
+    const INPUT: &str = "3 4 -1\n\
+                         4 3 -2\n\
+                         2 5 -1\n\
+                         1 3 7\n\
+                         3 9 -2\n\
+                         3 3 4\n\
+                         8 -2 0";
+
+    let path = std::env::temp_dir().join(format!("day01-{}-test.txt", std::process::id()));
+    std::fs::write(&path, INPUT).unwrap();
+    let path = path.to_str().unwrap();
+
+    let lists: Vec<Vec<i64>> = parse_lists(INPUT.as_bytes()).unwrap();
+    let metrics = Metrics {
+        distance: Box::new(RankWeightedDistance),
+        similarity: Box::new(CountSimilarity),
+    };
+    for (first, second) in [(0, 1), (1, 2), (2, 0)] {
+        let (list1, list2) = (&lists[first], &lists[second]);
+        let expected = (
+            calculate_total_distance(list1, list2, metrics.distance.as_ref()).unwrap(),
+            calculate_similarity_index(list1, list2, metrics.similarity.as_ref()).unwrap(),
+        );
+        for chunk_size in [1, 2, 3, 100] {
+            assert_eq!(
+                stream_totals::<i64>(path, first, second, chunk_size, &metrics).unwrap(),
+                expected
+            );
+        }
+    }
+    assert!(stream_totals::<i64>(path, 0, 3, 2, &Metrics::default()).is_err());
+
+    // More runs than can be merged at once
+    let input: String = (0..300i64)
+        .map(|k| format!("{} {}\n", (k * 37) % 101 - 50, (k * 11) % 7))
+        .collect();
+    std::fs::write(path, &input).unwrap();
+    let lists: Vec<Vec<i64>> = parse_lists(input.as_bytes()).unwrap();
+    let expected = (
+        calculate_total_distance(&lists[0], &lists[1], metrics.distance.as_ref()).unwrap(),
+        calculate_similarity_index(&lists[0], &lists[1], metrics.similarity.as_ref()).unwrap(),
+    );
+    for chunk_size in [1, 3, 300] {
+        assert_eq!(
+            stream_totals::<i64>(path, 0, 1, chunk_size, &metrics).unwrap(),
+            expected
+        );
+    }
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_metrics() {
+    let list1: [u64; 6] = [3, 4, 2, 1, 3, 3];
+    let list2: [u64; 6] = [4, 3, 5, 3, 9, 3];
+
+    let distance = |metric: &dyn DistanceMetric| calculate_total_distance(&list1, &list2, metric);
+    assert_eq!(distance(&SquaredDistance).unwrap(), 35);
+    assert_eq!(distance(&RankWeightedDistance).unwrap(), 48);
+
+    let weighted = WeightedSimilarity {
+        weights: HashMap::from([(3, 2)]),
+        default: 1,
+    };
+    assert_eq!(
+        calculate_similarity_index(&list1, &list2, &weighted).unwrap(),
+        27 * 2 + 4
+    );
+
+    let args = ["day01", "--distance", "squared"].map(String::from);
+    let metrics = parse_metrics(&args).unwrap();
+    assert_eq!(
+        calculate_total_distance(&list1, &list2, metrics.distance.as_ref()).unwrap(),
+        35
+    );
+    assert!(parse_metrics(&["day01", "--distance", "cosine"].map(String::from)).is_err());
+
+    // Sums that overflow are reported instead of wrapping around
+    let huge = [u64::MAX, u64::MAX];
+    assert!(calculate_total_distance(&huge, &[0, 0], &AbsoluteDistance).is_err());
+    assert!(calculate_total_distance(&huge, &[0, 0], &SquaredDistance).is_err());
+}
+
+#[test]
+fn test_explain() {
+    let list1: [u64; 6] = [3, 4, 2, 1, 3, 3];
+    let list2: [u64; 6] = [4, 3, 5, 3, 9, 3];
+
+    let explanation = explain(&list1, &list2, &Metrics::default()).unwrap();
+    assert_eq!(explanation.total_distance, 11);
+    assert_eq!(explanation.similarity_index, 31);
+    assert_eq!(
+        explanation.distances[5],
+        DistanceTerm {
+            rank: 5,
+            left: 4,
+            right: 9,
+            distance: 5
+        }
+    );
+    assert_eq!(
+        explanation.similarities[2],
+        SimilarityTerm {
+            id: 3,
+            left_count: 3,
+            right_count: 3,
+            contribution: 27
+        }
+    );
+    assert_eq!(explanation.similarities.len(), 4);
+
+    let table = explanation.table();
+    assert_eq!(table.lines().count(), 1 + 6 + 2 + 1 + 4 + 1);
+    assert!(table.ends_with("Similarity index: 31"));
+
+    let json = explanation.json();
+    assert!(json.starts_with(
+        "{\"distance\": {\"total\": 11, \"pairs\": [{\"rank\": 0, \"left\": 1, \"right\": 3, \"distance\": 2}, "
+    ));
+    assert!(json
+        .ends_with("{\"id\": 4, \"left_count\": 1, \"right_count\": 1, \"contribution\": 4}]}}"));
+
+    let args = |extra: &[&str]| {
+        ["day01"]
+            .iter()
+            .chain(extra)
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(parse_explain(&args(&[])).unwrap(), None);
+    assert_eq!(
+        parse_explain(&args(&["--explain", "json"])).unwrap(),
+        Some(ExplainFormat::Json)
+    );
+    assert!(parse_explain(&args(&["--explain"])).is_err());
+    assert!(parse_explain(&args(&["--explain", "xml"])).is_err());
+    assert!(parse_explain(&args(&["--stream", "--explain", "table"])).is_err());
+}
@@ -1,26 +1,158 @@
+mod utils;
+
 use std::{
+    collections::HashMap,
     fs::File,
     io::{BufRead, BufReader},
+    time::Instant,
 };
 
 use rayon::iter::{
     IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
 };
+use utils::cli::flag_value;
 
 const INPUT_FILE: &str = "input/day02.txt";
 
 fn main() {
     println!("Day 2");
 
+    let args: Vec<String> = std::env::args().collect();
+    if args.iter().any(|arg| arg == "--bench") {
+        bench_dampener(&args);
+        return;
+    }
+
     let reports = read_input(INPUT_FILE).expect("Failed to read input file");
+    let rules = SafetyRules::from_args(&args).expect("Invalid safety rules");
 
     // Part 1: Safe reports
-    let num_safe_reports = count_safe_reports(&reports, false);
+    let num_safe_reports = count_safe_reports(&reports, false, &rules);
     println!("[Part 1] {}", num_safe_reports);
 
     // Part 2: Safe reports with Problem Dampener
-    let num_safe_reports = count_safe_reports(&reports, true);
+    let num_safe_reports = count_safe_reports(&reports, true, &rules);
     println!("[Part 2] {}", num_safe_reports);
+
+    // Safe reports removing up to `--tolerance <k>` levels
+    if let Some(tolerance) = flag_value(&args, "--tolerance") {
+        let tolerance = tolerance.parse().expect("Tolerance must be a number");
+        let num_safe_reports = count_safe_reports_with_tolerance(&reports, tolerance, &rules);
+        println!("[Tolerance {}] {}", tolerance, num_safe_reports);
+    }
+
+    // Why every unsafe report fails, as `--diagnose table` or `--diagnose json`
+    if let Some(format) = flag_value(&args, "--diagnose") {
+        let diagnostics = diagnose(&reports, &rules);
+        match format {
+            "table" => println!("{}", diagnostics_table(&diagnostics)),
+            "json" => println!("{}", diagnostics_json(&diagnostics)),
+            _ => panic!("Unknown diagnostics format: {}", format),
+        }
+    }
+}
+
+/// Direction in which the levels of a safe report move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Increasing,
+    Decreasing,
+    /// Either one, set by the first step that is not a plateau.
+    Either,
+}
+
+/// Requirements that every pair of adjacent levels of a safe report must meet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SafetyRules {
+    /// Smallest difference between adjacent levels, plateaus aside.
+    min_step: u32,
+    /// Largest difference between adjacent levels.
+    max_step: u32,
+    direction: Direction,
+    /// Whether adjacent levels can be equal.
+    allow_plateaus: bool,
+}
+
+impl Default for SafetyRules {
+    fn default() -> Self {
+        Self {
+            min_step: 1,
+            max_step: 3,
+            direction: Direction::Either,
+            allow_plateaus: false,
+        }
+    }
+}
+
+impl SafetyRules {
+    // Reads the rules from the `--min-step <n>`, `--max-step <n>`,
+    // `--direction <increasing|decreasing|either>` and `--allow-plateaus` command line flags.
+    // Missing flags keep the puzzle rules.
+    fn from_args(args: &[String]) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut rules = Self::default();
+
+        if let Some(value) = flag_value(args, "--min-step") {
+            rules.min_step = value
+                .parse()
+                .map_err(|_| "Cannot convert min step to u32")?;
+        }
+        if let Some(value) = flag_value(args, "--max-step") {
+            rules.max_step = value
+                .parse()
+                .map_err(|_| "Cannot convert max step to u32")?;
+        }
+        if let Some(value) = flag_value(args, "--direction") {
+            rules.direction = match value {
+                "increasing" => Direction::Increasing,
+                "decreasing" => Direction::Decreasing,
+                "either" => Direction::Either,
+                _ => return Err(format!("Unknown direction: {}", value).into()),
+            };
+        }
+        rules.allow_plateaus = args.iter().any(|arg| arg == "--allow-plateaus");
+
+        if rules.min_step > rules.max_step {
+            return Err(format!(
+                "Min step {} is larger than max step {}",
+                rules.min_step, rules.max_step
+            )
+            .into());
+        }
+
+        Ok(rules)
+    }
+}
+
+/// A requirement broken by two adjacent levels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Violation {
+    Plateau,
+    WrongDirection,
+    StepTooSmall,
+    StepTooBig,
+}
+
+impl Violation {
+    fn name(self) -> &'static str {
+        match self {
+            Violation::Plateau => "plateau",
+            Violation::WrongDirection => "wrong_direction",
+            Violation::StepTooSmall => "step_too_small",
+            Violation::StepTooBig => "step_too_big",
+        }
+    }
+}
+
+/// Why a report is unsafe. Indices start at 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Diagnostic {
+    report: usize,
+    /// Index of the first level of the first pair that breaks a rule.
+    index: usize,
+    pair: (u32, u32),
+    violation: Violation,
+    /// Smallest index of a level whose removal makes the report safe, if any.
+    fixing_removal: Option<usize>,
 }
 
 fn read_input(file_path: &str) -> Result<Vec<Vec<u32>>, Box<dyn std::error::Error>> {
@@ -43,24 +175,31 @@ fn read_input(file_path: &str) -> Result<Vec<Vec<u32>>, Box<dyn std::error::Erro
 }
 
 // Check a report without changing it
-fn is_report_safe(report: &[u32]) -> bool {
-    let mut current_diff: i32 = 0;
+fn is_report_safe(report: &[u32], rules: &SafetyRules) -> bool {
+    first_violation(report.iter().copied(), rules).is_none()
+}
 
-    for pair in report.windows(2) {
-        let (ok, diff) = check_levels(pair[0], pair[1], current_diff);
-        if !ok {
-            return false;
-        } else {
-            current_diff += diff;
-        }
+// Check a report allowing one element to be removed
+fn is_report_safe_with_dampener(report: &[u32], rules: &SafetyRules) -> bool {
+    match first_violation(report.iter().copied(), rules) {
+        Some((index, _)) => fixing_removal(report, index, rules).is_some(),
+        None => true,
     }
+}
 
-    true
+// Smallest index of a level whose removal makes an unsafe report safe, given the index of its
+// first violation. Only the levels around the first violation, and the first level that sets the
+// direction, can make a difference, so at most four removals are tried without copying the report.
+fn fixing_removal(report: &[u32], index: usize, rules: &SafetyRules) -> Option<usize> {
+    [Some(0), index.checked_sub(1), Some(index), Some(index + 1)]
+        .into_iter()
+        .flatten()
+        .find(|&drop_index| first_violation(without(report, drop_index), rules).is_none())
 }
 
-// Check a report allowing one element to be removed
-fn is_report_safe_with_dampener(report: &[u32]) -> bool {
-    for drop_index in 0..=report.len() {
+// Check a report allowing one element to be removed, trying every element in turn
+fn is_report_safe_with_dampener_naive(report: &[u32], rules: &SafetyRules) -> bool {
+    for drop_index in 0..report.len() {
         let report_drop: Vec<u32> = report
             .to_vec()
             .into_par_iter()
@@ -69,7 +208,7 @@ fn is_report_safe_with_dampener(report: &[u32]) -> bool {
             .map(|(_, value)| value)
             .collect();
 
-        if is_report_safe(&report_drop) {
+        if is_report_safe(&report_drop, rules) {
             return true;
         }
     }
@@ -77,31 +216,461 @@ fn is_report_safe_with_dampener(report: &[u32]) -> bool {
     false
 }
 
-// Check that two adjacent levels meet the requirements
-fn check_levels(l0: u32, l1: u32, current_diff: i32) -> (bool, i32) {
-    const MIN_DIST: i32 = 1;
-    const MAX_DIST: i32 = 3;
+// Index of the first level of the first pair that does not meet the requirements, and the
+// requirement it breaks
+fn first_violation<I: Iterator<Item = u32>>(
+    levels: I,
+    rules: &SafetyRules,
+) -> Option<(usize, Violation)> {
+    let mut direction = rules.direction;
+    let mut previous = None;
+
+    for (index, level) in levels.enumerate() {
+        if let Some(previous) = previous {
+            match check_levels(previous, level, direction, rules) {
+                Ok(next_direction) => direction = next_direction,
+                Err(violation) => return Some((index - 1, violation)),
+            }
+        }
+        previous = Some(level);
+    }
+
+    None
+}
+
+// Levels of a report with one of them removed
+fn without(report: &[u32], drop_index: usize) -> impl Iterator<Item = u32> + '_ {
+    report
+        .iter()
+        .enumerate()
+        .filter(move |&(index, _)| index != drop_index)
+        .map(|(_, &level)| level)
+}
+
+// Minimum number of levels to remove so that the report becomes safe. The kept levels form the
+// longest chain of allowed steps in a single direction. The longest chain ending at each level
+// only depends on the best chains ending at the values within a step of it, which makes this
+// linear in the length of the report for bounded steps.
+fn min_removals(report: &[u32], rules: &SafetyRules) -> usize {
+    let mut longest_increasing: HashMap<u32, usize> = HashMap::new();
+    let mut longest_decreasing: HashMap<u32, usize> = HashMap::new();
+    let mut longest = 0;
+
+    // Steps of 0 are plateaus, which follow their own rule
+    let min_step = rules.min_step.max(1);
 
-    let dist: i32 = (l1 as i32) - (l0 as i32);
-    let dist_abs: i32 = dist.abs();
-    let next_diff = current_diff + dist;
+    for &level in report {
+        let plateau = |chains: &HashMap<u32, usize>| {
+            rules
+                .allow_plateaus
+                .then(|| chains.get(&level).copied())
+                .flatten()
+        };
 
-    let ok =
-        (MIN_DIST <= dist_abs) && (dist_abs <= MAX_DIST) && (next_diff.abs() > current_diff.abs());
+        let increasing = match rules.direction {
+            Direction::Decreasing => 0,
+            _ => {
+                let below = level.checked_sub(min_step).map(|high| {
+                    best_chain(
+                        &longest_increasing,
+                        level.saturating_sub(rules.max_step),
+                        high,
+                    )
+                });
+                1 + below
+                    .flatten()
+                    .max(plateau(&longest_increasing))
+                    .unwrap_or(0)
+            }
+        };
+        let decreasing = match rules.direction {
+            Direction::Increasing => 0,
+            _ => {
+                let above = level.checked_add(min_step).map(|low| {
+                    best_chain(
+                        &longest_decreasing,
+                        low,
+                        level.saturating_add(rules.max_step),
+                    )
+                });
+                1 + above
+                    .flatten()
+                    .max(plateau(&longest_decreasing))
+                    .unwrap_or(0)
+            }
+        };
 
-    (ok, dist)
+        let entry = longest_increasing.entry(level).or_insert(0);
+        *entry = (*entry).max(increasing);
+        let entry = longest_decreasing.entry(level).or_insert(0);
+        *entry = (*entry).max(decreasing);
+
+        longest = longest.max(increasing).max(decreasing);
+    }
+
+    report.len() - longest
+}
+
+// Longest chain ending at a value between `low` and `high`, looking up whichever is smaller: the
+// range or the chains found so far.
+fn best_chain(chains: &HashMap<u32, usize>, low: u32, high: u32) -> Option<usize> {
+    if low > high {
+        None
+    } else if ((high - low) as usize) < chains.len() {
+        (low..=high)
+            .filter_map(|value| chains.get(&value).copied())
+            .max()
+    } else {
+        chains
+            .iter()
+            .filter(|(&value, _)| (low..=high).contains(&value))
+            .map(|(_, &length)| length)
+            .max()
+    }
 }
 
-fn count_safe_reports(reports: &Vec<Vec<u32>>, use_problem_dampener: bool) -> usize {
+// Check that two adjacent levels meet the requirements, given the direction set by the previous
+// levels. Returns the direction set after them.
+fn check_levels(
+    l0: u32,
+    l1: u32,
+    direction: Direction,
+    rules: &SafetyRules,
+) -> Result<Direction, Violation> {
+    let step = l0.abs_diff(l1);
+    if step == 0 {
+        return if rules.allow_plateaus {
+            Ok(direction)
+        } else {
+            Err(Violation::Plateau)
+        };
+    }
+
+    let step_direction = if l1 > l0 {
+        Direction::Increasing
+    } else {
+        Direction::Decreasing
+    };
+
+    if (direction != Direction::Either) && (direction != step_direction) {
+        Err(Violation::WrongDirection)
+    } else if step < rules.min_step {
+        Err(Violation::StepTooSmall)
+    } else if step > rules.max_step {
+        Err(Violation::StepTooBig)
+    } else {
+        Ok(step_direction)
+    }
+}
+
+// Explains every unsafe report, in input order
+fn diagnose(reports: &[Vec<u32>], rules: &SafetyRules) -> Vec<Diagnostic> {
+    reports
+        .iter()
+        .enumerate()
+        .filter_map(|(report_index, report)| {
+            let (index, violation) = first_violation(report.iter().copied(), rules)?;
+            Some(Diagnostic {
+                report: report_index,
+                index,
+                pair: (report[index], report[index + 1]),
+                violation,
+                fixing_removal: fixing_removal(report, index, rules),
+            })
+        })
+        .collect()
+}
+
+fn diagnostics_table(diagnostics: &[Diagnostic]) -> String {
+    let mut output = format!(
+        "{:>8} {:>8} {:>12} {:>16} {:>8}",
+        "Report", "Index", "Pair", "Rule", "Removal"
+    );
+
+    for diagnostic in diagnostics {
+        output.push_str(&format!(
+            "\n{:>8} {:>8} {:>12} {:>16} {:>8}",
+            diagnostic.report,
+            diagnostic.index,
+            format!("{} {}", diagnostic.pair.0, diagnostic.pair.1),
+            diagnostic.violation.name(),
+            diagnostic
+                .fixing_removal
+                .map_or("-".to_string(), |index| index.to_string())
+        ));
+    }
+
+    output
+}
+
+fn diagnostics_json(diagnostics: &[Diagnostic]) -> String {
+    let entries: Vec<String> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            format!(
+                "{{\"report\": {}, \"index\": {}, \"pair\": [{}, {}], \"rule\": \"{}\", \"fixing_removal\": {}}}",
+                diagnostic.report,
+                diagnostic.index,
+                diagnostic.pair.0,
+                diagnostic.pair.1,
+                diagnostic.violation.name(),
+                diagnostic
+                    .fixing_removal
+                    .map_or("null".to_string(), |index| index.to_string())
+            )
+        })
+        .collect();
+
+    format!("[{}]", entries.join(", "))
+}
+
+fn count_safe_reports(
+    reports: &Vec<Vec<u32>>,
+    use_problem_dampener: bool,
+    rules: &SafetyRules,
+) -> usize {
     if use_problem_dampener {
         reports
             .par_iter()
-            .filter(|report| is_report_safe_with_dampener(report))
+            .filter(|report| is_report_safe_with_dampener(report, rules))
             .count()
     } else {
         reports
             .par_iter()
-            .filter(|report| is_report_safe(report))
+            .filter(|report| is_report_safe(report, rules))
             .count()
     }
 }
+
+// Times the Problem Dampener checks on `--reports <n>` generated reports of `--levels <n>` levels,
+// each broken by a single level near its end.
+fn bench_dampener(args: &[String]) {
+    let parse = |flag: &str, default: usize| {
+        flag_value(args, flag).map_or(default, |value| {
+            value.parse().expect("Benchmark sizes must be numbers")
+        })
+    };
+    let reports = generate_reports(parse("--reports", 100), parse("--levels", 1000), 2024);
+    let rules = SafetyRules::default();
+
+    let start = Instant::now();
+    let naive = reports
+        .iter()
+        .filter(|report| is_report_safe_with_dampener_naive(report, &rules))
+        .count();
+    let naive_time = start.elapsed();
+
+    let start = Instant::now();
+    let linear = reports
+        .iter()
+        .filter(|report| is_report_safe_with_dampener(report, &rules))
+        .count();
+    let linear_time = start.elapsed();
+
+    assert_eq!(naive, linear, "Both checks must agree");
+    println!("[Bench] Naive:  {:?}", naive_time);
+    println!("[Bench] Linear: {:?}", linear_time);
+    println!(
+        "[Bench] Speedup: {:.1}x",
+        naive_time.as_secs_f64() / linear_time.as_secs_f64()
+    );
+}
+
+// Generates safe increasing reports, then moves one level near the end out of place.
+fn generate_reports(count: usize, levels: usize, seed: u64) -> Vec<Vec<u32>> {
+    // Linear congruential generator, good enough for test data
+    let mut state = seed;
+    let mut random = move |bound: u32| {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((state >> 33) % bound as u64) as u32
+    };
+
+    (0..count)
+        .map(|_| {
+            let mut level = random(100);
+            let mut report: Vec<u32> = (0..levels)
+                .map(|_| {
+                    level += 1 + random(3);
+                    level
+                })
+                .collect();
+
+            if levels > 2 {
+                let broken = levels - 1 - random(levels.min(10) as u32) as usize;
+                report[broken] += 10 * random(2);
+            }
+            report
+        })
+        .collect()
+}
+
+fn count_safe_reports_with_tolerance(
+    reports: &[Vec<u32>],
+    tolerance: usize,
+    rules: &SafetyRules,
+) -> usize {
+    reports
+        .par_iter()
+        .filter(|report| min_removals(report, rules) <= tolerance)
+        .count()
+}
+
+#[test]
+fn test_example() {
+    let reports = vec![
+        vec![7, 6, 4, 2, 1],
+        vec![1, 2, 7, 8, 9],
+        vec![9, 7, 6, 2, 1],
+        vec![1, 3, 2, 4, 5],
+        vec![8, 6, 4, 4, 1],
+        vec![1, 3, 6, 7, 9],
+    ];
+    let rules = SafetyRules::default();
+
+    assert_eq!(count_safe_reports(&reports, false, &rules), 2);
+    assert_eq!(count_safe_reports(&reports, true, &rules), 4);
+
+    let removals: Vec<usize> = reports
+        .iter()
+        .map(|report| min_removals(report, &rules))
+        .collect();
+    assert_eq!(removals, vec![0, 2, 2, 1, 1, 0]);
+    assert_eq!(count_safe_reports_with_tolerance(&reports, 0, &rules), 2);
+    assert_eq!(count_safe_reports_with_tolerance(&reports, 1, &rules), 4);
+    assert_eq!(count_safe_reports_with_tolerance(&reports, 2, &rules), 6);
+
+    // Only a few removals need to be tried
+    for report in generate_reports(200, 12, 7).iter().chain(&reports) {
+        assert_eq!(
+            is_report_safe_with_dampener(report, &rules),
+            is_report_safe_with_dampener_naive(report, &rules)
+        );
+    }
+    assert!(is_report_safe_with_dampener(&[5, 6, 4, 3, 2], &rules));
+    assert!(is_report_safe_with_dampener(&[1, 5, 6, 7], &rules));
+    assert!(!is_report_safe_with_dampener(&[1, 2, 3, 9, 10, 11], &rules));
+
+    // Levels can be removed anywhere, and a single level is always safe
+    assert_eq!(min_removals(&[5, 1, 6, 9, 2, 7, 20, 9], &rules), 4);
+    assert_eq!(min_removals(&[4], &rules), 0);
+    assert_eq!(min_removals(&[], &rules), 0);
+}
+
+#[test]
+fn test_safety_rules() {
+    let rules = SafetyRules::default();
+
+    // The direction is set by the first step, whatever the size of the next ones
+    assert!(!is_report_safe(&[5, 6, 3], &rules));
+
+    let args = [
+        "day02",
+        "--max-step",
+        "5",
+        "--direction",
+        "increasing",
+        "--allow-plateaus",
+    ]
+    .map(String::from);
+    let relaxed = SafetyRules::from_args(&args).unwrap();
+    assert_eq!(
+        relaxed,
+        SafetyRules {
+            min_step: 1,
+            max_step: 5,
+            direction: Direction::Increasing,
+            allow_plateaus: true,
+        }
+    );
+
+    let reports = vec![
+        vec![1, 1, 6, 7, 7],
+        vec![1, 2, 8, 9],
+        vec![9, 8, 7],
+        vec![1, 3, 2, 2, 4],
+    ];
+    assert_eq!(count_safe_reports(&reports, false, &rules), 1);
+    assert_eq!(count_safe_reports(&reports, false, &relaxed), 1);
+    assert_eq!(count_safe_reports(&reports, true, &relaxed), 2);
+    assert_eq!(count_safe_reports_with_tolerance(&reports, 1, &relaxed), 2);
+    assert_eq!(min_removals(&reports[2], &relaxed), 2);
+    assert_eq!(min_removals(&reports[3], &relaxed), 1);
+
+    assert_eq!(
+        check_levels(4, 4, Direction::Either, &rules),
+        Err(Violation::Plateau)
+    );
+    assert_eq!(
+        check_levels(4, 3, Direction::Increasing, &rules),
+        Err(Violation::WrongDirection)
+    );
+    assert_eq!(
+        check_levels(4, 8, Direction::Either, &rules),
+        Err(Violation::StepTooBig)
+    );
+
+    let wide = ["day02", "--min-step", "2", "--max-step", "1000000"].map(String::from);
+    let wide = SafetyRules::from_args(&wide).unwrap();
+    assert_eq!(
+        check_levels(4, 5, Direction::Either, &wide),
+        Err(Violation::StepTooSmall)
+    );
+    assert_eq!(min_removals(&[1, 2, 3, 500000, 7], &wide), 2);
+
+    // The dampener still only needs a few removals, and agrees with the removal count
+    for rules in [relaxed, wide] {
+        for report in generate_reports(200, 10, 11).iter().chain(&reports) {
+            let safe = is_report_safe_with_dampener_naive(report, &rules);
+            assert_eq!(is_report_safe_with_dampener(report, &rules), safe);
+            assert_eq!(min_removals(report, &rules) <= 1, safe);
+        }
+    }
+
+    let invalid = ["day02", "--min-step", "4"].map(String::from);
+    assert!(SafetyRules::from_args(&invalid).is_err());
+}
+
+#[test]
+fn test_diagnostics() {
+    let reports = vec![
+        vec![7, 6, 4, 2, 1],
+        vec![1, 2, 7, 8, 9],
+        vec![9, 7, 6, 2, 1],
+        vec![1, 3, 2, 4, 5],
+        vec![8, 6, 4, 4, 1],
+        vec![1, 3, 6, 7, 9],
+    ];
+
+    let diagnostics = diagnose(&reports, &SafetyRules::default());
+    assert_eq!(diagnostics.len(), 4);
+    assert_eq!(
+        diagnostics[0],
+        Diagnostic {
+            report: 1,
+            index: 1,
+            pair: (2, 7),
+            violation: Violation::StepTooBig,
+            fixing_removal: None,
+        }
+    );
+    assert_eq!(diagnostics[2].violation, Violation::WrongDirection);
+    assert_eq!(diagnostics[2].fixing_removal, Some(1));
+    assert_eq!(diagnostics[3].violation, Violation::Plateau);
+    assert_eq!(diagnostics[3].fixing_removal, Some(2));
+
+    let table = diagnostics_table(&diagnostics);
+    assert_eq!(table.lines().count(), 5);
+    assert!(table
+        .lines()
+        .nth(3)
+        .unwrap()
+        .ends_with("wrong_direction        1"));
+
+    let json = diagnostics_json(&diagnostics);
+    assert!(json.starts_with(
+        "[{\"report\": 1, \"index\": 1, \"pair\": [2, 7], \"rule\": \"step_too_big\", \"fixing_removal\": null}, "
+    ));
+    assert!(json.ends_with("\"rule\": \"plateau\", \"fixing_removal\": 2}]"));
+}
@@ -1,6 +1,8 @@
-use std::collections::HashSet;
+mod utils;
 
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use utils::bits::{BitGrid, MaskGrid};
+use utils::{direction_index, Direction, Grid, Point};
 
 const INPUT_FILE: &str = "input/day06.txt";
 
@@ -15,8 +17,7 @@ const RIGHT: Direction = (1, 0);
 const DOWN: Direction = (0, 1);
 const LEFT: Direction = (-1, 0);
 
-type Position = (i32, i32);
-type Direction = (i32, i32);
+type Position = Point;
 
 fn turn_right(dir: &Direction) -> Direction {
     (-dir.1, dir.0)
@@ -24,23 +25,14 @@ fn turn_right(dir: &Direction) -> Direction {
 
 #[derive(Debug, Clone)]
 struct Map {
-    block: Vec<Vec<char>>,
+    grid: Grid,
 }
 
 impl Map {
     fn from_string(text: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let mut block = Vec::with_capacity(140);
-
-        for line in text.lines() {
-            let mut char_line: Vec<char> = Vec::with_capacity(140);
-            for ch in line.chars() {
-                char_line.push(ch);
-            }
-
-            block.push(char_line);
-        }
-
-        Ok(Self { block })
+        Ok(Self {
+            grid: Grid::from_string(text)?,
+        })
     }
 
     fn from_file(file_name: &str) -> Result<Self, Box<dyn std::error::Error>> {
@@ -48,22 +40,20 @@ impl Map {
     }
 
     fn at(&self, pos: Position) -> char {
-        self.block[pos.1 as usize][pos.0 as usize]
-    }
-
-    fn dimensions(&self) -> (i32, i32) {
-        (self.block[0].len() as i32, self.block.len() as i32)
+        *self
+            .grid
+            .get_point(pos)
+            .expect("Position outside of the map")
     }
 
     fn is_inside(&self, position: Position) -> bool {
-        let dimensions = self.dimensions();
-        (position.0 >= 0 && position.0 < dimensions.0)
-            && (position.1 >= 0 && position.1 < dimensions.1)
+        self.grid.is_inside(position.0, position.1)
     }
 
-    // Returns a HashSet with all the positions visited by the guard.
-    fn get_visited_positions(&self) -> HashSet<Position> {
-        let mut visited_positions = HashSet::new();
+    // Returns a BitGrid with all the positions visited by the guard.
+    fn get_visited_positions(&self) -> BitGrid {
+        let (columns, rows) = self.grid.dimensions();
+        let mut visited_positions = BitGrid::new(columns, rows);
         let (mut pos, mut dir) = self.find_guard().expect("Expected a guard");
 
         loop {
@@ -85,26 +75,26 @@ impl Map {
 
     // Find the guard and its direction in the map.
     fn find_guard(&self) -> Option<(Position, Direction)> {
-        for (j, row) in self.block.iter().enumerate() {
-            for (i, _) in row.iter().enumerate() {
-                let ch = self.at((i as i32, j as i32));
-                let pos = (i as i32, j as i32);
-                match ch {
-                    CELL_GUARD_UP => return Some((pos, UP)),
-                    CELL_GUARD_RIGHT => return Some((pos, RIGHT)),
-                    CELL_GUARD_DOWN => return Some((pos, DOWN)),
-                    CELL_GUARD_LEFT => return Some((pos, LEFT)),
-                    _ => (),
-                }
-            }
-        }
-
-        None
+        let pos = self.grid.find_by(|ch| {
+            matches!(
+                *ch,
+                CELL_GUARD_UP | CELL_GUARD_RIGHT | CELL_GUARD_DOWN | CELL_GUARD_LEFT
+            )
+        })?;
+
+        let dir = match self.at(pos) {
+            CELL_GUARD_UP => UP,
+            CELL_GUARD_RIGHT => RIGHT,
+            CELL_GUARD_DOWN => DOWN,
+            _ => LEFT,
+        };
+
+        Some((pos, dir))
     }
 
     // Calculate how many different obstacles in the map make the guard loop.
     fn calculate_posible_obstacles(&self) -> (usize, usize) {
-        let visited_positions = self.get_visited_positions();
+        let visited_positions: Vec<Position> = self.get_visited_positions().iter().collect();
         let num_posible_obstacles = visited_positions
             .par_iter() // Parallel iterator
             .filter(|&position| self.check_obstacle(*position))
@@ -114,20 +104,21 @@ impl Map {
     }
 
     // Check if an obstacle produces a loop
-    fn check_obstacle(&self, obstacle_pos: (i32, i32)) -> bool {
+    fn check_obstacle(&self, obstacle_pos: Position) -> bool {
         let (mut pos, mut dir) = self.find_guard().expect("Expected a guard");
         if pos == obstacle_pos {
             return false;
         }
 
-        let mut past_positions: HashSet<(Position, Direction)> = HashSet::new();
+        // Past positions, with the directions in which the guard crossed them
+        let (columns, rows) = self.grid.dimensions();
+        let mut past_positions = MaskGrid::new(columns, rows);
         loop {
-            if past_positions.contains(&(pos, dir)) {
+            let state = direction_index(dir).expect("Expected an orthogonal direction");
+            if !past_positions.insert(pos, state) {
                 return true;
             }
 
-            past_positions.insert((pos, dir));
-
             let next_position = (pos.0 + dir.0, pos.1 + dir.1);
             if !self.is_inside(next_position) {
                 break;
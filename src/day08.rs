@@ -23,20 +23,7 @@ fn main() {
 }
 
 fn get_antena_positions(map: &Grid) -> HashMap<char, Vec<Position>> {
-    let mut antena_positions: HashMap<char, Vec<Position>> = HashMap::new();
-
-    for (j, row) in map.iter_rows().enumerate() {
-        for (i, ch) in row.iter().enumerate() {
-            if ch.is_alphanumeric() {
-                antena_positions
-                    .entry(*ch)
-                    .or_default()
-                    .push((i as isize, j as isize));
-            }
-        }
-    }
-
-    antena_positions
+    map.group_positions_by(|ch| ch.is_alphanumeric())
 }
 
 fn calculate_antinodes(
@@ -45,30 +32,22 @@ fn calculate_antinodes(
     p1: Position,
     include_resonant: bool,
 ) -> Vec<Position> {
-    let mut antinodes = Vec::new();
     let d10 = (p0.0 - p1.0, p0.1 - p1.1);
     let d01 = (p1.0 - p0.0, p1.1 - p0.1);
 
-    let mut k01 = if include_resonant { 1 } else { 2 };
-    let mut k10 = k01;
-    loop {
-        let a01 = (p0.0 + k01 * d01.0, p0.1 + k01 * d01.1);
-        if !map.is_inside(a01.0, a01.1) || (!include_resonant && k01 > 2) {
-            break;
-        }
-        antinodes.push(a01);
-        k01 += 1;
-    }
-    loop {
-        let a10 = (p1.0 + k10 * d10.0, p1.1 + k10 * d10.1);
-        if !map.is_inside(a10.0, a10.1) || (!include_resonant && k10 > 2) {
-            break;
-        }
-        antinodes.push(a10);
-        k10 += 1;
-    }
-
-    antinodes
+    // Resonant antinodes include the antenas themselves and repeat until the edge of the map
+    let (skip, take) = if include_resonant {
+        (0, usize::MAX)
+    } else {
+        (1, 1)
+    };
+
+    map.ray(p1, d01)
+        .skip(skip)
+        .take(take)
+        .chain(map.ray(p0, d10).skip(skip).take(take))
+        .map(|(position, _)| position)
+        .collect()
 }
 
 fn calculate_antinodes_for_map(map: &Grid, include_resonant: bool) -> HashSet<Position> {
@@ -105,7 +84,7 @@ fn test_example() {
                         ............\n\
                         ............";
 
-    let map = Grid::from_string(GRID);
+    let map = Grid::from_string(GRID).expect("Could not read map");
     assert_eq!(calculate_antinodes_for_map(&map, false).len(), 14);
     assert_eq!(calculate_antinodes_for_map(&map, true).len(), 34);
 }
@@ -16,37 +16,65 @@ fn main() {
     println!("[Part 2] {}", total_rating);
 }
 
-fn parse_map(input: &str) -> Map {
-    input
-        .lines()
-        .map(|line| {
-            line.chars()
-                .map(|c| c.to_digit(10).unwrap() as u8)
-                .collect()
-        })
-        .collect()
+fn parse_map(input: &str) -> Result<Map, Box<dyn std::error::Error>> {
+    let mut map: Map = Vec::new();
+
+    for (row, line) in input.lines().enumerate() {
+        let heights = line
+            .chars()
+            .enumerate()
+            .map(|(column, c)| match c.to_digit(10) {
+                Some(height) => Ok(height as u8),
+                None => Err(format!(
+                    "Line {}, column {}: '{}' is not a height",
+                    row + 1,
+                    column + 1,
+                    c
+                )),
+            })
+            .collect::<Result<Vec<u8>, String>>()?;
+
+        if let Some(first) = map.first() {
+            if heights.len() != first.len() {
+                return Err(format!(
+                    "Line {}: expected {} heights, found {}",
+                    row + 1,
+                    first.len(),
+                    heights.len()
+                )
+                .into());
+            }
+        }
+        map.push(heights);
+    }
+
+    if map.is_empty() || map[0].is_empty() {
+        return Err("The map is empty".into());
+    }
+
+    Ok(map)
 }
 
 fn parse_input(file_path: &str) -> Result<Map, Box<dyn std::error::Error>> {
     let input = std::fs::read_to_string(file_path)?;
-    Ok(parse_map(&input))
+    parse_map(&input)
 }
 
 fn bfs_trailhead(map: &Map, start: (isize, isize)) -> Vec<(isize, isize)> {
     let mut endpoints = Vec::new();
 
-    let map_width = map[0].len() as isize;
     let map_height = map.len() as isize;
+    let map_width = map[0].len() as isize;
 
     let mut queue = VecDeque::with_capacity(9);
     queue.push_back((start, 0));
 
     while let Some(((i, j), height)) = queue.pop_front() {
         for (di, dj) in DIRECTIONS {
-            let ni = i as isize + di;
-            let nj = j as isize + dj;
+            let ni = i + di;
+            let nj = j + dj;
 
-            if (ni < 0) || (ni >= map_width) || (nj < 0) || (nj >= map_height) {
+            if (ni < 0) || (ni >= map_height) || (nj < 0) || (nj >= map_width) {
                 continue;
             }
 
@@ -96,8 +124,28 @@ fn test_example() {
                          01329801\n\
                          10456732";
 
-    let map = parse_map(INPUT);
+    let map = parse_map(INPUT).unwrap();
     let (total_score, total_rating) = calculate_trails(&map);
     assert_eq!(total_score, 36);
     assert_eq!(total_rating, 81);
+
+    // Maps that are not square
+    let map = parse_map("0123456789").unwrap();
+    assert_eq!(calculate_trails(&map), (1, 1));
+    let map = parse_map("01\n32\n45\n76\n89").unwrap();
+    assert_eq!(calculate_trails(&map), (1, 1));
+}
+
+#[test]
+fn test_invalid_map() {
+    assert!(parse_map("").is_err());
+    assert!(parse_map("\n").is_err());
+    assert_eq!(
+        parse_map("012\n34").unwrap_err().to_string(),
+        "Line 2: expected 3 heights, found 2"
+    );
+    assert_eq!(
+        parse_map("012\n3.5").unwrap_err().to_string(),
+        "Line 2, column 2: '.' is not a height"
+    );
 }
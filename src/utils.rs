@@ -1,81 +1,501 @@
 #![allow(dead_code)]
 
+pub mod bits;
+pub mod cli;
+pub mod cycle;
+pub mod diff;
+pub mod distance;
+pub mod image;
+pub mod ndgrid;
+pub mod pattern;
+pub mod ray;
+pub mod recorder;
+pub mod region;
+pub mod render;
+pub mod search;
+pub mod sparse;
+pub mod view;
+
+use std::fmt;
+use std::fs;
+use std::ops::{Index, IndexMut};
 use std::path::Path;
-use std::{fs, isize};
+use std::str::FromStr;
+
+/// A position in a grid as (column, row).
+pub type Point = (isize, isize);
+
+/// A step between positions as (column, row) offsets.
+pub type Direction = (isize, isize);
+
+/// The four orthogonal directions, clockwise from up.
+pub const DIRECTIONS_4: [Direction; 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// The four orthogonal and four diagonal directions, clockwise from right.
+pub const DIRECTIONS_8: [Direction; 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
 
-#[derive(Debug, Clone)]
-pub struct Grid {
-    block: Vec<Vec<char>>,
+/// Gets the index of an orthogonal direction in `DIRECTIONS_4`.
+pub fn direction_index(dir: Direction) -> Option<usize> {
+    DIRECTIONS_4.iter().position(|&d| d == dir)
 }
 
-impl Grid {
-    /// Creates a new Grid from a 2D vector of characters.
-    pub fn new(block: Vec<Vec<char>>) -> Self {
-        Self { block }
+/// Errors produced when building a Grid from malformed input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridError {
+    /// The input has no rows or no columns.
+    Empty,
+    /// A row does not have the same width as the first row.
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// A carriage return that is not part of a `\r\n` line ending.
+    CarriageReturn { row: usize, column: usize },
+    /// The number of cells does not match the dimensions.
+    CellCount { expected: usize, found: usize },
+    /// Two grids that should have the same dimensions, as (columns, rows), do not.
+    DimensionMismatch {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridError::Empty => write!(f, "Grid is empty"),
+            GridError::RaggedRow {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Row {} has {} columns, expected {}",
+                row, found, expected
+            ),
+            GridError::CarriageReturn { row, column } => {
+                write!(f, "Stray carriage return at row {}, column {}", row, column)
+            }
+            GridError::CellCount { expected, found } => {
+                write!(f, "Found {} cells, expected {}", found, expected)
+            }
+            GridError::DimensionMismatch { expected, found } => write!(
+                f,
+                "Grid is {}x{}, expected {}x{}",
+                found.0, found.1, expected.0, expected.1
+            ),
+        }
     }
+}
+
+impl std::error::Error for GridError {}
+
+/// How points outside of the grid are resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Addressing {
+    /// Points outside of the grid have no cell.
+    #[default]
+    Bounded,
+    /// Points wrap around the edges, as if the grid was a torus.
+    Wrapping,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawGrid<T>"))]
+pub struct Grid<T = char> {
+    block: Vec<Vec<T>>,
+    addressing: Addressing,
+}
+
+// Deserialized fields of a Grid, before they are checked by `Grid::new`.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct RawGrid<T> {
+    block: Vec<Vec<T>>,
+    #[serde(default)]
+    addressing: Addressing,
+}
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<RawGrid<T>> for Grid<T> {
+    type Error = GridError;
 
-    /// Reads a character from the block at (i, j), where `i` is the column and `j` is the row.
-    pub fn get(&self, i: usize, j: usize) -> Option<char> {
-        self.block.get(j).and_then(|row| row.get(i).copied())
+    fn try_from(raw: RawGrid<T>) -> Result<Self, GridError> {
+        Ok(Self::new(raw.block)?.with_addressing(raw.addressing))
     }
+}
 
-    /// Writes a character to the block at (i, j), where `i` is the column and `j` is the row.
-    pub fn set(&mut self, i: usize, j: usize, value: char) {
-        if let Some(row) = self.block.get_mut(j) {
-            if i < row.len() {
-                row[i] = value;
-            }
+impl<T> Grid<T> {
+    /// Creates a new Grid from a 2D vector of cells. All rows must have the same, non-zero width.
+    pub fn new(block: Vec<Vec<T>>) -> Result<Self, GridError> {
+        let columns = block.first().map_or(0, |row| row.len());
+        if columns == 0 {
+            return Err(GridError::Empty);
         }
+
+        if let Some((row, found)) = block
+            .iter()
+            .map(|row| row.len())
+            .enumerate()
+            .find(|&(_, len)| len != columns)
+        {
+            return Err(GridError::RaggedRow {
+                row,
+                expected: columns,
+                found,
+            });
+        }
+
+        Ok(Self::from_block(block))
     }
 
-    /// Iterates over the characters row by row.
-    pub fn iter_rows(&self) -> impl Iterator<Item = &Vec<char>> {
-        self.block.iter()
+    /// Creates a new Grid from a 2D vector of cells, padding rows shorter than the widest one
+    /// with `fill`.
+    pub fn new_padded(mut block: Vec<Vec<T>>, fill: T) -> Result<Self, GridError>
+    where
+        T: Clone,
+    {
+        let columns = block.iter().map(|row| row.len()).max().unwrap_or(0);
+        for row in block.iter_mut() {
+            row.resize(columns, fill.clone());
+        }
+
+        Self::new(block)
     }
 
-    /// Iterates over the characters row by row.
-    pub fn iter_rows_enumerate(&self) -> impl Iterator<Item = (usize, &Vec<char>)> {
-        self.block.iter().enumerate()
+    /// Creates a Grid of the given dimensions with every cell set to `value`. Both dimensions
+    /// must be non-zero.
+    pub fn filled(columns: usize, rows: usize, value: T) -> Result<Self, GridError>
+    where
+        T: Clone,
+    {
+        Self::new(vec![vec![value; columns]; rows])
     }
 
-    /// Iterates over all characters in the block, row by row.
-    pub fn iter_chars(&self) -> impl Iterator<Item = char> + '_ {
-        self.block.iter().flat_map(|row| row.iter().copied())
+    // A grid with the same shape and addressing, with every cell set to `value`.
+    fn filled_like<U: Clone>(&self, value: U) -> Grid<U> {
+        let (columns, rows) = self.dimensions();
+        Grid::from_block(vec![vec![value; columns]; rows]).with_addressing(self.addressing)
     }
 
-    /// Reads a Grid from a string, splitting rows by newlines.
-    pub fn from_string(input: &str) -> Self {
-        let block = input
-            .lines()
-            .map(|line| line.chars().collect::<Vec<char>>())
-            .collect::<Vec<Vec<char>>>();
-        Self::new(block)
+    // Wraps an already rectangular block.
+    fn from_block(block: Vec<Vec<T>>) -> Self {
+        Self {
+            block,
+            addressing: Addressing::default(),
+        }
     }
 
-    /// Reads a Grid from a file.
-    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
-        let content = fs::read_to_string(path)?;
-        Ok(Self::from_string(&content))
+    /// Selects how points outside of the grid are resolved.
+    pub fn with_addressing(mut self, addressing: Addressing) -> Self {
+        self.addressing = addressing;
+        self
+    }
+
+    pub fn addressing(&self) -> Addressing {
+        self.addressing
     }
 
-    /// Converts the Grid back into a string representation.
-    pub fn to_string(&self) -> String {
+    /// Resolves `p` to the point of the cell it addresses, or None if it addresses no cell.
+    pub fn normalize(&self, p: Point) -> Option<Point> {
+        let (columns, rows) = self.dimensions();
+        match self.addressing {
+            Addressing::Bounded => self.is_inside(p.0, p.1).then_some(p),
+            Addressing::Wrapping if (columns == 0) || (rows == 0) => None,
+            Addressing::Wrapping => Some((
+                p.0.rem_euclid(columns as isize),
+                p.1.rem_euclid(rows as isize),
+            )),
+        }
+    }
+
+    /// Reads a cell from the block at (i, j), where `i` is the column and `j` is the row.
+    pub fn get(&self, i: usize, j: usize) -> Option<T>
+    where
+        T: Copy,
+    {
+        self.get_point((i as isize, j as isize)).copied()
+    }
+
+    /// Reads a reference to the cell at point `p`, if it addresses a cell.
+    pub fn get_point(&self, p: Point) -> Option<&T> {
+        let (i, j) = self.normalize(p)?;
         self.block
+            .get(j as usize)
+            .and_then(|row| row.get(i as usize))
+    }
+
+    /// Writes a cell to the block at (i, j), where `i` is the column and `j` is the row.
+    pub fn set(&mut self, i: usize, j: usize, value: T) {
+        if let Some((i, j)) = self.normalize((i as isize, j as isize)) {
+            self.block[j as usize][i as usize] = value;
+        }
+    }
+
+    /// Iterates over the cells row by row.
+    pub fn iter_rows(&self) -> impl Iterator<Item = &Vec<T>> {
+        self.block.iter()
+    }
+
+    /// Iterates over the cells row by row.
+    pub fn iter_rows_enumerate(&self) -> impl Iterator<Item = (usize, &Vec<T>)> {
+        self.block.iter().enumerate()
+    }
+
+    /// Iterates over all cells in the block, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.block.iter().flatten()
+    }
+
+    /// Iterates over all points in the block, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        self.block
+            .iter()
+            .enumerate()
+            .flat_map(|(j, row)| (0..row.len()).map(move |i| (i as isize, j as isize)))
+    }
+
+    /// Iterates over the orthogonal neighbors of `p` that address a cell, resolved as in
+    /// `normalize`.
+    pub fn neighbors(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        DIRECTIONS_4
             .iter()
-            .map(|row| row.iter().collect::<String>())
-            .collect::<Vec<String>>()
-            .join("\n")
+            .filter_map(move |d| self.normalize((p.0 + d.0, p.1 + d.1)))
     }
 
     /// Gets the dimensions of the block as (columns, rows).
     pub fn dimensions(&self) -> (usize, usize) {
         let rows = self.block.len();
-        let columns = self.block.get(0).map_or(0, |row| row.len());
+        let columns = self.block.first().map_or(0, |row| row.len());
         (columns, rows)
     }
 
+    /// Checks whether (i, j) lies within the grid rectangle, regardless of the addressing mode.
     pub fn is_inside(&self, i: isize, j: isize) -> bool {
         let dim = self.dimensions();
         (i >= 0) && (i < dim.0 as isize) && (j >= 0) && (j < dim.1 as isize)
     }
 }
+
+impl Grid<char> {
+    /// Iterates over all characters in the block, row by row.
+    pub fn iter_chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.block.iter().flat_map(|row| row.iter().copied())
+    }
+
+    /// Reads a Grid from a string, splitting rows by newlines.
+    pub fn from_string(input: &str) -> Result<Self, GridError> {
+        Self::new(Self::parse_block(input)?)
+    }
+
+    /// Reads a Grid from a string, padding rows shorter than the widest one with `fill`.
+    pub fn from_string_padded(input: &str, fill: char) -> Result<Self, GridError> {
+        Self::new_padded(Self::parse_block(input)?, fill)
+    }
+
+    /// Reads a Grid from a file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path)?;
+        Ok(Self::from_string(&content)?)
+    }
+
+    fn parse_block(input: &str) -> Result<Vec<Vec<char>>, GridError> {
+        input
+            .lines()
+            .enumerate()
+            .map(|(row, line)| match line.find('\r') {
+                Some(column) => Err(GridError::CarriageReturn { row, column }),
+                None => Ok(line.chars().collect()),
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Grid<char> {
+    type Err = GridError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_string(s)
+    }
+}
+
+/// Writes the cells row by row, with rows separated by newlines.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (j, row) in self.block.iter().enumerate() {
+            if j > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Accesses the cell addressed by a point. Panics if the point addresses no cell.
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &T {
+        self.get_point(p)
+            .unwrap_or_else(|| panic!("Point {:?} is outside of the grid", p))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        let (i, j) = self
+            .normalize(p)
+            .unwrap_or_else(|| panic!("Point {:?} is outside of the grid", p));
+        &mut self.block[j as usize][i as usize]
+    }
+}
+
+/// Iterates over the cells row by row.
+impl<T> IntoIterator for Grid<T> {
+    type Item = T;
+    type IntoIter = std::iter::Flatten<std::vec::IntoIter<Vec<T>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.block.into_iter().flatten()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Grid<T> {
+    type Item = &'a T;
+    type IntoIter = std::iter::Flatten<std::slice::Iter<'a, Vec<T>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.block.iter().flatten()
+    }
+}
+
+#[test]
+fn test_grid_validation() {
+    assert_eq!(Grid::from_string("").unwrap_err(), GridError::Empty);
+    assert_eq!(Grid::from_string("\n\n").unwrap_err(), GridError::Empty);
+    assert_eq!(
+        Grid::from_string("abc\nab\nabc").unwrap_err(),
+        GridError::RaggedRow {
+            row: 1,
+            expected: 3,
+            found: 2
+        }
+    );
+    assert_eq!(
+        Grid::from_string("abc\na\rc").unwrap_err(),
+        GridError::CarriageReturn { row: 1, column: 1 }
+    );
+
+    let grid = Grid::from_string("abc\r\ndef\r\n").unwrap();
+    assert_eq!(grid.dimensions(), (3, 2));
+
+    let padded = Grid::from_string_padded("abc\na\nab", '.').unwrap();
+    assert_eq!(padded.to_string(), "abc\na..\nab.");
+}
+
+#[test]
+fn test_addressing() {
+    const GRID: &str = "abc\n\
+                        def";
+
+    let bounded = Grid::from_string(GRID).unwrap();
+    assert!(bounded.is_inside(0, 0));
+    assert!(bounded.is_inside(2, 1));
+    assert!(!bounded.is_inside(-1, 0));
+    assert!(!bounded.is_inside(3, 1));
+    assert!(!bounded.is_inside(0, 2));
+    assert_eq!(bounded.get_point((-1, 0)), None);
+    assert_eq!(bounded.get(3, 0), None);
+    assert_eq!(bounded.neighbors((0, 0)).count(), 2);
+
+    let mut wrapping = bounded.clone().with_addressing(Addressing::Wrapping);
+    assert_eq!(wrapping.get_point((-1, 0)), Some(&'c'));
+    assert_eq!(wrapping.get(3, 1), Some('d'));
+    assert_eq!(wrapping.get_point((-4, -3)), Some(&'f'));
+    assert_eq!(wrapping.normalize((5, -1)), Some((2, 1)));
+    assert_eq!(
+        wrapping.neighbors((0, 0)).collect::<Vec<_>>(),
+        vec![(0, 1), (1, 0), (0, 1), (2, 0)]
+    );
+
+    wrapping.set(4, 2, 'x');
+    assert_eq!(wrapping.get(1, 0), Some('x'));
+}
+
+#[test]
+fn test_traits() {
+    use std::collections::HashSet;
+
+    const GRID: &str = "ab\n\
+                        cd";
+
+    let mut grid: Grid = GRID.parse().unwrap();
+    assert_eq!(grid.to_string(), GRID);
+    assert_eq!(format!("{}", Grid::filled(2, 2, 0).unwrap()), "00\n00");
+    assert_eq!(Grid::filled(0, 2, 0).unwrap_err(), GridError::Empty);
+    assert_eq!(Grid::filled(2, 0, 0).unwrap_err(), GridError::Empty);
+
+    assert_eq!(grid[(1, 0)], 'b');
+    grid[(0, 1)] = 'x';
+    assert_eq!(grid.get(0, 1), Some('x'));
+
+    let wrapping = grid.clone().with_addressing(Addressing::Wrapping);
+    assert_eq!(wrapping[(-1, -1)], 'd');
+    assert_ne!(wrapping, grid);
+
+    let mut seen = HashSet::new();
+    assert!(seen.insert(grid.clone()));
+    assert!(!seen.insert(GRID.replace('c', "x").parse().unwrap()));
+
+    assert_eq!((&grid).into_iter().collect::<String>(), "abxd");
+    assert_eq!(grid.into_iter().collect::<String>(), "abxd");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    let grid = Grid::from_string("ab\ncd")
+        .unwrap()
+        .with_addressing(Addressing::Wrapping);
+    let json = serde_json::to_string(&grid).unwrap();
+    assert_eq!(
+        json,
+        r#"{"block":[["a","b"],["c","d"]],"addressing":"Wrapping"}"#
+    );
+    assert_eq!(serde_json::from_str::<Grid>(&json).unwrap(), grid);
+
+    let ragged = r#"{"block":[[1,2],[3]],"addressing":"Bounded"}"#;
+    let error = serde_json::from_str::<Grid<u8>>(ragged).unwrap_err();
+    assert!(error.to_string().starts_with(
+        &GridError::RaggedRow {
+            row: 1,
+            expected: 2,
+            found: 1
+        }
+        .to_string()
+    ));
+
+    let bounded = r#"{"block":[[1,2],[3,4]]}"#;
+    let grid = serde_json::from_str::<Grid<u8>>(bounded).unwrap();
+    assert_eq!(grid.addressing(), Addressing::Bounded);
+    assert_eq!(grid[(1, 1)], 4);
+
+    let empty = r#"{"block":[],"addressing":"Bounded"}"#;
+    assert!(serde_json::from_str::<Grid<u8>>(empty).is_err());
+}
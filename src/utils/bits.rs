@@ -0,0 +1,272 @@
+use super::Point;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A set of points of a fixed-size grid, stored as one bit per cell.
+///
+/// Points outside of the grid are never contained and cannot be inserted.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    columns: usize,
+    rows: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    /// Creates an empty set for a grid of `columns` x `rows` cells.
+    pub fn new(columns: usize, rows: usize) -> Self {
+        Self {
+            columns,
+            rows,
+            words: vec![0; (columns * rows).div_ceil(WORD_BITS)],
+        }
+    }
+
+    // Index of the word and bit for point `p`, if it is inside the grid.
+    fn locate(&self, p: Point) -> Option<(usize, u64)> {
+        let inside =
+            (p.0 >= 0) && (p.0 < self.columns as isize) && (p.1 >= 0) && (p.1 < self.rows as isize);
+        if !inside {
+            return None;
+        }
+
+        let index = p.1 as usize * self.columns + p.0 as usize;
+        Some((index / WORD_BITS, 1 << (index % WORD_BITS)))
+    }
+
+    /// Gets the dimensions of the grid as (columns, rows).
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.columns, self.rows)
+    }
+
+    /// Adds `p` to the set. Returns whether it was not already present.
+    pub fn insert(&mut self, p: Point) -> bool {
+        match self.locate(p) {
+            Some((word, bit)) => {
+                let inserted = self.words[word] & bit == 0;
+                self.words[word] |= bit;
+                inserted
+            }
+            None => false,
+        }
+    }
+
+    /// Removes `p` from the set. Returns whether it was present.
+    pub fn remove(&mut self, p: Point) -> bool {
+        match self.locate(p) {
+            Some((word, bit)) => {
+                let removed = self.words[word] & bit != 0;
+                self.words[word] &= !bit;
+                removed
+            }
+            None => false,
+        }
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        self.locate(p)
+            .is_some_and(|(word, bit)| self.words[word] & bit != 0)
+    }
+
+    /// Removes all points.
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    /// Number of points in the set.
+    pub fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    /// Iterates over the points in the set, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = Point> + '_ {
+        self.words.iter().enumerate().flat_map(move |(w, &word)| {
+            let mut bits = word;
+            std::iter::from_fn(move || {
+                if bits == 0 {
+                    return None;
+                }
+
+                let index = w * WORD_BITS + bits.trailing_zeros() as usize;
+                bits &= bits - 1;
+                Some((
+                    (index % self.columns) as isize,
+                    (index / self.columns) as isize,
+                ))
+            })
+        })
+    }
+}
+
+/// A set of (point, state) pairs of a fixed-size grid, stored as a mask of up to eight states
+/// per cell. Useful to track e.g. the directions in which each cell has been crossed.
+///
+/// Points outside of the grid and states above 7 are never contained and cannot be inserted.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MaskGrid {
+    columns: usize,
+    rows: usize,
+    masks: Vec<u8>,
+}
+
+impl MaskGrid {
+    /// Number of different states that each cell can hold.
+    pub const STATES: usize = u8::BITS as usize;
+
+    /// Creates an empty set for a grid of `columns` x `rows` cells.
+    pub fn new(columns: usize, rows: usize) -> Self {
+        Self {
+            columns,
+            rows,
+            masks: vec![0; columns * rows],
+        }
+    }
+
+    // Index of the cell for point `p` and bit for `state`, if both are valid.
+    fn locate(&self, p: Point, state: usize) -> Option<(usize, u8)> {
+        let inside =
+            (p.0 >= 0) && (p.0 < self.columns as isize) && (p.1 >= 0) && (p.1 < self.rows as isize);
+        if !inside || state >= Self::STATES {
+            return None;
+        }
+
+        Some((p.1 as usize * self.columns + p.0 as usize, 1 << state))
+    }
+
+    /// Gets the dimensions of the grid as (columns, rows).
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.columns, self.rows)
+    }
+
+    /// Adds `state` to point `p`. Returns whether it was not already present.
+    pub fn insert(&mut self, p: Point, state: usize) -> bool {
+        match self.locate(p, state) {
+            Some((cell, bit)) => {
+                let inserted = self.masks[cell] & bit == 0;
+                self.masks[cell] |= bit;
+                inserted
+            }
+            None => false,
+        }
+    }
+
+    /// Removes `state` from point `p`. Returns whether it was present.
+    pub fn remove(&mut self, p: Point, state: usize) -> bool {
+        match self.locate(p, state) {
+            Some((cell, bit)) => {
+                let removed = self.masks[cell] & bit != 0;
+                self.masks[cell] &= !bit;
+                removed
+            }
+            None => false,
+        }
+    }
+
+    pub fn contains(&self, p: Point, state: usize) -> bool {
+        self.locate(p, state)
+            .is_some_and(|(cell, bit)| self.masks[cell] & bit != 0)
+    }
+
+    /// Gets the mask of all the states of point `p`, with bit `k` set for state `k`.
+    pub fn mask(&self, p: Point) -> u8 {
+        self.locate(p, 0).map_or(0, |(cell, _)| self.masks[cell])
+    }
+
+    /// Removes all states from all points.
+    pub fn clear(&mut self) {
+        self.masks.fill(0);
+    }
+
+    /// Number of (point, state) pairs in the set.
+    pub fn len(&self) -> usize {
+        self.masks
+            .iter()
+            .map(|mask| mask.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.masks.iter().all(|&mask| mask == 0)
+    }
+
+    /// Iterates over the points with at least one state, row by row, with their masks.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, u8)> + '_ {
+        self.masks
+            .iter()
+            .enumerate()
+            .filter(|(_, &mask)| mask != 0)
+            .map(|(cell, &mask)| {
+                let p = (
+                    (cell % self.columns) as isize,
+                    (cell / self.columns) as isize,
+                );
+                (p, mask)
+            })
+    }
+
+    /// Projects the set into the points with at least one state.
+    pub fn to_bit_grid(&self) -> BitGrid {
+        let mut bits = BitGrid::new(self.columns, self.rows);
+        for (p, _) in self.iter() {
+            bits.insert(p);
+        }
+
+        bits
+    }
+}
+
+#[test]
+fn test_bit_grid() {
+    let mut bits = BitGrid::new(10, 13);
+    assert!(bits.insert((0, 0)));
+    assert!(!bits.insert((0, 0)));
+    assert!(bits.insert((9, 12)));
+    assert!(bits.insert((3, 6)));
+    assert!(!bits.insert((10, 0)));
+    assert!(!bits.insert((-1, 0)));
+
+    assert!(bits.contains((3, 6)));
+    assert!(!bits.contains((6, 3)));
+    assert_eq!(bits.len(), 3);
+    assert_eq!(
+        bits.iter().collect::<Vec<_>>(),
+        vec![(0, 0), (3, 6), (9, 12)]
+    );
+
+    assert!(bits.remove((3, 6)));
+    assert!(!bits.remove((3, 6)));
+    assert_eq!(bits.len(), 2);
+
+    bits.clear();
+    assert!(bits.is_empty());
+}
+
+#[test]
+fn test_mask_grid() {
+    let mut states = MaskGrid::new(4, 3);
+    assert!(states.insert((1, 2), 0));
+    assert!(states.insert((1, 2), 3));
+    assert!(!states.insert((1, 2), 3));
+    assert!(!states.insert((1, 2), MaskGrid::STATES));
+    assert!(states.insert((3, 0), 1));
+
+    assert!(states.contains((1, 2), 3));
+    assert!(!states.contains((1, 2), 1));
+    assert_eq!(states.mask((1, 2)), 0b1001);
+    assert_eq!(states.len(), 3);
+    assert_eq!(
+        states.iter().collect::<Vec<_>>(),
+        vec![((3, 0), 0b10), ((1, 2), 0b1001)]
+    );
+    assert_eq!(states.to_bit_grid().len(), 2);
+
+    assert!(states.remove((1, 2), 0));
+    assert_eq!(states.mask((1, 2)), 0b1000);
+}
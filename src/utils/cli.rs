@@ -0,0 +1,15 @@
+/// Gets the value following `flag` on the command line, if any.
+pub fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|k| args.get(k + 1))
+        .map(|value| value.as_str())
+}
+
+#[test]
+fn test_flag_value() {
+    let args = ["day01", "--columns", "1,3", "--stream"].map(String::from);
+    assert_eq!(flag_value(&args, "--columns"), Some("1,3"));
+    assert_eq!(flag_value(&args, "--stream"), None);
+    assert_eq!(flag_value(&args, "--explain"), None);
+}
@@ -0,0 +1,186 @@
+//! Cycle detection for iterated simulations.
+//!
+//! A simulation is described by its initial state and a transition function that returns the
+//! next state, or None when the simulation ends. Deterministic simulations that never end must
+//! eventually repeat a state, and from then on they go around the same cycle forever.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A cycle in the sequence of states x0, x1 = next(x0), x2 = next(x1), ...
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cycle {
+    /// Index of the first state that belongs to the cycle.
+    pub start: usize,
+    /// Number of states in the cycle.
+    pub length: usize,
+}
+
+impl Cycle {
+    /// Maps the index of any state to the index of the first state equal to it.
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+}
+
+/// Finds the cycle with Floyd's tortoise and hare algorithm, using constant memory. Returns None
+/// if the simulation ends.
+pub fn floyd<S, F>(initial: S, next: F) -> Option<Cycle>
+where
+    S: PartialEq,
+    F: Fn(&S) -> Option<S>,
+{
+    let next_2 = |state: &S| next(state).and_then(|state| next(&state));
+
+    // Meet somewhere inside the cycle
+    let mut tortoise = next(&initial)?;
+    let mut hare = next_2(&initial)?;
+    while tortoise != hare {
+        tortoise = next(&tortoise)?;
+        hare = next_2(&hare)?;
+    }
+
+    // The start of the cycle is as far from the initial state as from the meeting point
+    let mut start = 0;
+    let mut tortoise = initial;
+    while tortoise != hare {
+        tortoise = next(&tortoise)?;
+        hare = next(&hare)?;
+        start += 1;
+    }
+
+    let mut length = 1;
+    let mut hare = next(&tortoise)?;
+    while tortoise != hare {
+        hare = next(&hare)?;
+        length += 1;
+    }
+
+    Some(Cycle { start, length })
+}
+
+/// Finds the cycle with Brent's algorithm, using constant memory and usually fewer transitions
+/// than Floyd's. Returns None if the simulation ends.
+pub fn brent<S, F>(initial: S, next: F) -> Option<Cycle>
+where
+    S: Clone + PartialEq,
+    F: Fn(&S) -> Option<S>,
+{
+    // Search for the length in windows of increasing powers of two
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = next(&initial)?;
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = next(&hare)?;
+        length += 1;
+    }
+
+    // Walk two states one cycle apart until they meet at the start
+    let mut tortoise = initial.clone();
+    let mut hare = initial;
+    for _ in 0..length {
+        hare = next(&hare)?;
+    }
+
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = next(&tortoise)?;
+        hare = next(&hare)?;
+        start += 1;
+    }
+
+    Some(Cycle { start, length })
+}
+
+/// Finds the cycle by remembering every visited state, which needs the fewest transitions.
+/// Returns None if the simulation ends.
+pub fn hashed<S, F>(initial: S, next: F) -> Option<Cycle>
+where
+    S: Eq + Hash,
+    F: Fn(&S) -> Option<S>,
+{
+    let mut seen = HashMap::new();
+    let mut state = initial;
+
+    for index in 0.. {
+        if let Some(&start) = seen.get(&state) {
+            return Some(Cycle {
+                start,
+                length: index - start,
+            });
+        }
+
+        let following = next(&state)?;
+        seen.insert(state, index);
+        state = following;
+    }
+
+    unreachable!()
+}
+
+/// Gets the state after `n` transitions, skipping whole turns of the cycle once it is found.
+/// Returns None if the simulation ends before that.
+pub fn nth_state<S, F>(initial: S, next: F, n: usize) -> Option<S>
+where
+    S: Clone + Eq + Hash,
+    F: Fn(&S) -> Option<S>,
+{
+    let mut seen = HashMap::new();
+    let mut states = Vec::new();
+    let mut state = initial;
+
+    for index in 0..n {
+        if let Some(&start) = seen.get(&state) {
+            let cycle = Cycle {
+                start,
+                length: index - start,
+            };
+            return Some(states.swap_remove(cycle.reduce(n)));
+        }
+
+        seen.insert(state.clone(), index);
+        states.push(state.clone());
+        state = next(&state)?;
+    }
+
+    Some(state)
+}
+
+#[test]
+fn test_cycle_detection() {
+    // 3 -> 10 -> 101 -> 2 -> 5 -> 26 -> 167 -> 95 -> 101 -> ...
+    let next = |x: &u64| Some((x * x + 1) % 255);
+
+    let expected = Cycle {
+        start: 2,
+        length: 6,
+    };
+    assert_eq!(floyd(3, next), Some(expected));
+    assert_eq!(brent(3, next), Some(expected));
+    assert_eq!(hashed(3, next), Some(expected));
+    assert_eq!(expected.reduce(10), 4);
+
+    let mut state = 3;
+    for _ in 0..1000 {
+        state = next(&state).unwrap();
+    }
+    assert_eq!(nth_state(3, next, 1000), Some(state));
+    assert_eq!(nth_state(3, next, 2), Some(101));
+
+    let ends = |x: &u64| (*x < 10).then_some(x + 1);
+    assert_eq!(floyd(0, ends), None);
+    assert_eq!(brent(0, ends), None);
+    assert_eq!(hashed(0, ends), None);
+    assert_eq!(nth_state(0, ends, 11), None);
+    assert_eq!(nth_state(0, ends, 10), Some(10));
+}
@@ -0,0 +1,152 @@
+use super::render::{Color, Renderer};
+use super::{Grid, GridError, Point};
+
+const REMOVED: Color = Color::RED;
+const ADDED: Color = Color::GREEN;
+
+/// A cell that holds a different value in two grids.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CellChange<T> {
+    pub point: Point,
+    pub old: T,
+    pub new: T,
+}
+
+/// The cells that changed between two grids of the same dimensions.
+#[derive(Debug, Clone)]
+pub struct GridDiff<'a, T> {
+    before: &'a Grid<T>,
+    after: &'a Grid<T>,
+    changes: Vec<CellChange<T>>,
+}
+
+impl<T: Clone + PartialEq> Grid<T> {
+    /// Compares the grid with `other`, cell by cell. Both grids must have the same dimensions.
+    pub fn diff<'a>(&'a self, other: &'a Grid<T>) -> Result<GridDiff<'a, T>, GridError> {
+        if self.dimensions() != other.dimensions() {
+            return Err(GridError::DimensionMismatch {
+                expected: self.dimensions(),
+                found: other.dimensions(),
+            });
+        }
+
+        let changes = self
+            .points()
+            .zip(self.iter().zip(other.iter()))
+            .filter(|(_, (old, new))| old != new)
+            .map(|(point, (old, new))| CellChange {
+                point,
+                old: old.clone(),
+                new: new.clone(),
+            })
+            .collect();
+
+        Ok(GridDiff {
+            before: self,
+            after: other,
+            changes,
+        })
+    }
+}
+
+impl<T: PartialEq> GridDiff<'_, T> {
+    /// Gets the changed cells, row by row.
+    pub fn changes(&self) -> &[CellChange<T>] {
+        &self.changes
+    }
+
+    /// Iterates over the points of the changed cells, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        self.changes.iter().map(|change| change.point)
+    }
+
+    /// Number of changed cells.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Draws the new grid with the changed cells highlighted, using `glyph` for every cell.
+    pub fn render_overlay<F>(&self, glyph: F) -> String
+    where
+        F: Fn(&T) -> char,
+    {
+        Renderer::with_glyphs(self.after, glyph)
+            .highlight(self.points(), ADDED)
+            .render()
+    }
+
+    /// Draws the old and the new grid next to each other, highlighting the changed cells in
+    /// both, using `glyph` for every cell.
+    pub fn render_side_by_side<F>(&self, glyph: F) -> String
+    where
+        F: Fn(&T) -> char,
+    {
+        let before = Renderer::with_glyphs(self.before, &glyph)
+            .highlight(self.points(), REMOVED)
+            .render();
+        let after = Renderer::with_glyphs(self.after, &glyph)
+            .highlight(self.points(), ADDED)
+            .render();
+
+        before
+            .lines()
+            .zip(after.lines())
+            .map(|(left, right)| format!("{} | {}", left, right))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl GridDiff<'_, char> {
+    /// Draws the new grid with the changed cells highlighted.
+    pub fn overlay(&self) -> String {
+        self.render_overlay(|ch| *ch)
+    }
+
+    /// Draws the old and the new grid next to each other, highlighting the changed cells.
+    pub fn side_by_side(&self) -> String {
+        self.render_side_by_side(|ch| *ch)
+    }
+}
+
+#[test]
+fn test_diff() {
+    let before = Grid::from_string("..#\n...").unwrap();
+    let after = Grid::from_string(".O#\n..#").unwrap();
+
+    let diff = before.diff(&after).unwrap();
+    assert_eq!(
+        diff.changes(),
+        &[
+            CellChange {
+                point: (1, 0),
+                old: '.',
+                new: 'O'
+            },
+            CellChange {
+                point: (2, 1),
+                old: '.',
+                new: '#'
+            },
+        ]
+    );
+    assert!(before.diff(&before).unwrap().is_empty());
+
+    assert!(diff.overlay().contains("\x1b[48;2;133;153;0mO\x1b[0m"));
+    let side_by_side = diff.side_by_side();
+    assert_eq!(side_by_side.lines().count(), 2);
+    assert!(side_by_side.starts_with(".\x1b[48;2;220;50;47m.\x1b[0m# | ."));
+
+    let other = Grid::from_string("...").unwrap();
+    assert_eq!(
+        before.diff(&other).unwrap_err(),
+        GridError::DimensionMismatch {
+            expected: (3, 2),
+            found: (3, 1)
+        }
+    );
+}
@@ -0,0 +1,105 @@
+use std::collections::VecDeque;
+
+use super::{Grid, Point};
+
+impl<T> Grid<T> {
+    /// Computes the number of orthogonal steps from the nearest of `sources` to every cell, or
+    /// None for unreachable cells. A step from a cell to its neighbor is allowed when
+    /// `passable(current, neighbor)` holds.
+    pub fn distances_from<I, F>(&self, sources: I, passable: F) -> Grid<Option<u32>>
+    where
+        I: IntoIterator<Item = Point>,
+        F: Fn(&T, &T) -> bool,
+    {
+        self.distance_field(sources, passable)
+    }
+
+    /// Computes the number of orthogonal steps from every cell to the nearest of `targets`, or
+    /// None if no target can be reached. Steps are allowed as in `distances_from`, so the search
+    /// follows them backwards from the targets.
+    pub fn distances_to<I, F>(&self, targets: I, passable: F) -> Grid<Option<u32>>
+    where
+        I: IntoIterator<Item = Point>,
+        F: Fn(&T, &T) -> bool,
+    {
+        self.distance_field(targets, |current, neighbor| passable(neighbor, current))
+    }
+
+    // Breadth-first search from all sources at once, expanding to a neighbor when
+    // `expand(current, neighbor)` holds.
+    fn distance_field<I, F>(&self, sources: I, expand: F) -> Grid<Option<u32>>
+    where
+        I: IntoIterator<Item = Point>,
+        F: Fn(&T, &T) -> bool,
+    {
+        let mut distances = self.filled_like(None);
+        let mut queue = VecDeque::new();
+
+        for source in sources {
+            if let Some(p) = self.normalize(source) {
+                if distances.get_point(p) == Some(&None) {
+                    distances[p] = Some(0);
+                    queue.push_back((p, 0));
+                }
+            }
+        }
+
+        while let Some((p, distance)) = queue.pop_front() {
+            let value = self
+                .get_point(p)
+                .expect("Queued points are inside the grid");
+
+            for n in self.neighbors(p) {
+                if distances.get_point(n) != Some(&None) {
+                    continue;
+                }
+
+                if let Some(neighbor) = self.get_point(n) {
+                    if expand(value, neighbor) {
+                        distances[n] = Some(distance + 1);
+                        queue.push_back((n, distance + 1));
+                    }
+                }
+            }
+        }
+
+        distances
+    }
+}
+
+#[test]
+fn test_distances() {
+    const MAZE: &str = "S.#\n\
+                        .##\n\
+                        ..E";
+
+    let maze = Grid::from_string(MAZE).unwrap();
+    let open = |_: &char, next: &char| *next != '#';
+
+    let from_start = maze.distances_from([(0, 0)], open);
+    assert_eq!(from_start.get_point((2, 2)), Some(&Some(4)));
+    assert_eq!(from_start.get_point((2, 0)), Some(&None));
+
+    let from_corners = maze.distances_from([(0, 0), (2, 2)], open);
+    assert_eq!(from_corners.get_point((0, 2)), Some(&Some(2)));
+    assert_eq!(from_corners.get_point((1, 2)), Some(&Some(1)));
+
+    // Trails climb exactly one unit of height per step
+    const HEIGHTS: &str = "0123\n\
+                           1234\n\
+                           8765\n\
+                           9876";
+
+    let heights = Grid::from_string(HEIGHTS).unwrap();
+    let climb = |current: &char, next: &char| *next as u32 == *current as u32 + 1;
+
+    let summits = heights.find_all(&'9').collect::<Vec<_>>();
+    let to_summit = heights.distances_to(summits, climb);
+    assert_eq!(to_summit.get_point((0, 0)), Some(&Some(9)));
+    assert_eq!(to_summit.get_point((0, 1)), Some(&Some(8)));
+    assert_eq!(to_summit.get_point((3, 0)), Some(&Some(6)));
+
+    let from_trailhead = heights.distances_from([(0, 0)], climb);
+    assert_eq!(from_trailhead.get_point((0, 3)), Some(&Some(9)));
+    assert_eq!(from_trailhead.get_point((1, 0)), Some(&Some(1)));
+}
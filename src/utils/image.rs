@@ -0,0 +1,156 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use super::render::{Color, Renderer, Style};
+use super::Grid;
+
+/// An RGB raster image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    /// Creates an image of `width` x `height` pixels filled with `color`.
+    pub fn new(width: usize, height: usize, color: Color) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![color; width * height],
+        }
+    }
+
+    /// Gets the dimensions of the image as (width, height).
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Reads the pixel at (x, y).
+    pub fn get(&self, x: usize, y: usize) -> Option<Color> {
+        (x < self.width && y < self.height).then(|| self.pixels[y * self.width + x])
+    }
+
+    /// Writes the pixel at (x, y), if it is inside the image.
+    pub fn set(&mut self, x: usize, y: usize, color: Color) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = color;
+        }
+    }
+
+    /// Gets the pixels as consecutive RGB bytes, row by row.
+    pub fn rgb_bytes(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|Color(r, g, b)| [*r, *g, *b])
+            .collect()
+    }
+
+    /// Writes the image in binary PPM (P6) format.
+    pub fn write_ppm<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        writer.write_all(&self.rgb_bytes())
+    }
+
+    /// Writes the image in PNG format.
+    pub fn write_png<W: Write>(&self, writer: W) -> Result<(), Box<dyn std::error::Error>> {
+        let mut encoder = png::Encoder::new(writer, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut png_writer = encoder.write_header()?;
+        png_writer.write_image_data(&self.rgb_bytes())?;
+        Ok(())
+    }
+
+    /// Saves the image to a file, in PPM or PNG format depending on its extension.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("ppm") => {
+                let mut writer = BufWriter::new(File::create(path)?);
+                self.write_ppm(&mut writer)?;
+                writer.flush()?;
+                Ok(())
+            }
+            Some("png") => self.write_png(BufWriter::new(File::create(path)?)),
+            _ => Err(format!("Unsupported image format: {}", path.display()).into()),
+        }
+    }
+}
+
+impl<T: PartialEq> Renderer<'_, T> {
+    /// Draws the grid as an image with `scale` x `scale` pixels per cell. Each cell is painted
+    /// with its overlay background if any, otherwise with its palette or path color, otherwise
+    /// black.
+    pub fn to_image(&self, scale: usize) -> Image {
+        image_from_styles(&self.styles(), scale)
+    }
+
+    /// Draws the grid as an image and saves it, in PPM or PNG format depending on the extension.
+    pub fn save_image<P: AsRef<Path>>(
+        &self,
+        path: P,
+        scale: usize,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.to_image(scale).save(path)
+    }
+}
+
+/// Draws resolved cell styles as an image with `scale` x `scale` pixels per cell. Each cell is
+/// painted with its background if any, otherwise with its foreground, otherwise black.
+pub fn image_from_styles(styles: &Grid<Style>, scale: usize) -> Image {
+    let (columns, rows) = styles.dimensions();
+    let mut image = Image::new(columns * scale, rows * scale, Color::BLACK);
+
+    for (j, row) in styles.iter_rows_enumerate() {
+        for (i, style) in row.iter().enumerate() {
+            let color = style
+                .background
+                .or(style.foreground)
+                .unwrap_or(Color::BLACK);
+
+            for y in (j * scale)..((j + 1) * scale) {
+                for x in (i * scale)..((i + 1) * scale) {
+                    image.set(x, y, color);
+                }
+            }
+        }
+    }
+
+    image
+}
+
+#[test]
+fn test_image_export() {
+    let grid = Grid::from_string("#.\n..").unwrap();
+    let image = Renderer::new(&grid)
+        .color('#', Color::RED)
+        .color('.', Color::GRAY)
+        .highlight([(1, 1)], Color::BLUE)
+        .to_image(3);
+
+    assert_eq!(image.dimensions(), (6, 6));
+    assert_eq!(image.get(2, 2), Some(Color::RED));
+    assert_eq!(image.get(3, 0), Some(Color::GRAY));
+    assert_eq!(image.get(5, 5), Some(Color::BLUE));
+    assert_eq!(image.get(6, 0), None);
+
+    let mut ppm = Vec::new();
+    image.write_ppm(&mut ppm).unwrap();
+    assert!(ppm.starts_with(b"P6\n6 6\n255\n"));
+    assert_eq!(ppm.len(), "P6\n6 6\n255\n".len() + 6 * 6 * 3);
+
+    let mut png = Vec::new();
+    image.write_png(&mut png).unwrap();
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+    assert!(image.save("grid.bmp").is_err());
+}
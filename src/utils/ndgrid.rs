@@ -0,0 +1,239 @@
+use std::ops::{Index, IndexMut};
+
+use super::{Addressing, GridError};
+
+/// A point of an N-dimensional grid, with one coordinate per axis.
+pub type NdPoint<const N: usize> = [isize; N];
+
+/// A dense N-dimensional grid. Axis 0 varies fastest, so in 2D the axes are (column, row) as in
+/// Grid.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NdGrid<T, const N: usize> {
+    dimensions: [usize; N],
+    cells: Vec<T>,
+    addressing: Addressing,
+}
+
+/// A 3D grid, indexed by (x, y, z).
+pub type Grid3<T> = NdGrid<T, 3>;
+
+impl<T, const N: usize> NdGrid<T, N> {
+    /// Creates a grid from its cells in storage order, where axis 0 varies fastest. There must be
+    /// exactly one cell per point and no axis can be empty.
+    pub fn new(dimensions: [usize; N], cells: Vec<T>) -> Result<Self, GridError> {
+        if dimensions.contains(&0) {
+            return Err(GridError::Empty);
+        }
+
+        let expected = dimensions.iter().product();
+        if cells.len() != expected {
+            return Err(GridError::CellCount {
+                expected,
+                found: cells.len(),
+            });
+        }
+
+        Ok(Self {
+            dimensions,
+            cells,
+            addressing: Addressing::default(),
+        })
+    }
+
+    /// Creates a grid of the given dimensions with every cell set to `value`.
+    pub fn filled(dimensions: [usize; N], value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            dimensions,
+            cells: vec![value; dimensions.iter().product()],
+            addressing: Addressing::default(),
+        }
+    }
+
+    /// Selects how points outside of the grid are resolved.
+    pub fn with_addressing(mut self, addressing: Addressing) -> Self {
+        self.addressing = addressing;
+        self
+    }
+
+    pub fn addressing(&self) -> Addressing {
+        self.addressing
+    }
+
+    /// Gets the length of every axis.
+    pub fn dimensions(&self) -> [usize; N] {
+        self.dimensions
+    }
+
+    /// Number of cells in the grid.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Checks whether `p` lies within the grid box, regardless of the addressing mode.
+    pub fn is_inside(&self, p: NdPoint<N>) -> bool {
+        p.iter()
+            .zip(self.dimensions)
+            .all(|(&x, length)| (x >= 0) && (x < length as isize))
+    }
+
+    /// Resolves `p` to the point of the cell it addresses, or None if it addresses no cell.
+    pub fn normalize(&self, p: NdPoint<N>) -> Option<NdPoint<N>> {
+        match self.addressing {
+            Addressing::Bounded => self.is_inside(p).then_some(p),
+            Addressing::Wrapping if self.cells.is_empty() => None,
+            Addressing::Wrapping => {
+                let mut wrapped = p;
+                for (x, length) in wrapped.iter_mut().zip(self.dimensions) {
+                    *x = x.rem_euclid(length as isize);
+                }
+                Some(wrapped)
+            }
+        }
+    }
+
+    /// Reads a reference to the cell at point `p`, if it addresses a cell.
+    pub fn get(&self, p: NdPoint<N>) -> Option<&T> {
+        self.offset(p).map(|offset| &self.cells[offset])
+    }
+
+    /// Reads a mutable reference to the cell at point `p`, if it addresses a cell.
+    pub fn get_mut(&mut self, p: NdPoint<N>) -> Option<&mut T> {
+        self.offset(p).map(|offset| &mut self.cells[offset])
+    }
+
+    /// Writes the cell at point `p`, if it addresses a cell.
+    pub fn set(&mut self, p: NdPoint<N>, value: T) {
+        if let Some(cell) = self.get_mut(p) {
+            *cell = value;
+        }
+    }
+
+    /// Iterates over all cells in storage order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// Iterates over all points in storage order, where axis 0 varies fastest.
+    pub fn points(&self) -> impl Iterator<Item = NdPoint<N>> + '_ {
+        (0..self.cells.len()).map(|offset| self.point(offset))
+    }
+
+    /// Iterates over all points and their cells in storage order.
+    pub fn iter_points(&self) -> impl Iterator<Item = (NdPoint<N>, &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(offset, cell)| (self.point(offset), cell))
+    }
+
+    /// Iterates over the 2N neighbors of `p` along a single axis that address a cell, resolved
+    /// as in `normalize`.
+    pub fn neighbors(&self, p: NdPoint<N>) -> impl Iterator<Item = NdPoint<N>> + '_ {
+        (0..N).flat_map(move |axis| {
+            [-1, 1].into_iter().filter_map(move |delta| {
+                let mut n = p;
+                n[axis] += delta;
+                self.normalize(n)
+            })
+        })
+    }
+
+    /// Iterates over the 3^N - 1 neighbors of `p`, diagonals included, that address a cell,
+    /// resolved as in `normalize`.
+    pub fn neighbors_all(&self, p: NdPoint<N>) -> impl Iterator<Item = NdPoint<N>> + '_ {
+        (0..3usize.pow(N as u32)).filter_map(move |code| {
+            let mut n = p;
+            let mut code = code;
+            for x in n.iter_mut() {
+                *x += (code % 3) as isize - 1;
+                code /= 3;
+            }
+            (n != p).then(|| self.normalize(n)).flatten()
+        })
+    }
+
+    // Position of the cell addressed by `p` in the storage vector.
+    fn offset(&self, p: NdPoint<N>) -> Option<usize> {
+        let p = self.normalize(p)?;
+        let mut offset = 0;
+        for (&x, length) in p.iter().zip(self.dimensions).rev() {
+            offset = offset * length + x as usize;
+        }
+        Some(offset)
+    }
+
+    // Point of the cell at position `offset` of the storage vector.
+    fn point(&self, mut offset: usize) -> NdPoint<N> {
+        let mut p = [0; N];
+        for (x, length) in p.iter_mut().zip(self.dimensions) {
+            *x = (offset % length) as isize;
+            offset /= length;
+        }
+        p
+    }
+}
+
+/// Accesses the cell addressed by a point. Panics if the point addresses no cell.
+impl<T, const N: usize> Index<NdPoint<N>> for NdGrid<T, N> {
+    type Output = T;
+
+    fn index(&self, p: NdPoint<N>) -> &T {
+        self.get(p)
+            .unwrap_or_else(|| panic!("Point {:?} is outside of the grid", p))
+    }
+}
+
+impl<T, const N: usize> IndexMut<NdPoint<N>> for NdGrid<T, N> {
+    fn index_mut(&mut self, p: NdPoint<N>) -> &mut T {
+        self.get_mut(p)
+            .unwrap_or_else(|| panic!("Point {:?} is outside of the grid", p))
+    }
+}
+
+#[test]
+fn test_nd_grid() {
+    assert_eq!(
+        Grid3::new([2, 2, 2], vec![0; 7]).unwrap_err(),
+        GridError::CellCount {
+            expected: 8,
+            found: 7
+        }
+    );
+    assert_eq!(
+        Grid3::<u8>::new([2, 0, 2], Vec::new()).unwrap_err(),
+        GridError::Empty
+    );
+
+    let grid = Grid3::new([2, 3, 4], (0..24).collect()).unwrap();
+    assert_eq!(grid[[1, 2, 3]], 23);
+    assert_eq!(grid.get([1, 0, 1]), Some(&7));
+    assert_eq!(grid.get([2, 0, 0]), None);
+    assert_eq!(grid.points().nth(7), Some([1, 0, 1]));
+    assert!(grid.iter_points().all(|(p, &cell)| grid[p] == cell));
+
+    assert_eq!(grid.neighbors([0, 0, 0]).count(), 3);
+    assert_eq!(grid.neighbors([1, 1, 1]).count(), 5);
+    assert_eq!(grid.neighbors_all([0, 0, 0]).count(), 7);
+    assert_eq!(grid.neighbors_all([1, 1, 1]).count(), 17);
+
+    let mut torus = Grid3::filled([3, 3, 3], false).with_addressing(Addressing::Wrapping);
+    torus.set([-1, 3, 0], true);
+    assert!(torus[[2, 0, 0]]);
+    assert_eq!(torus.neighbors_all([1, 1, 1]).count(), 26);
+    assert_eq!(
+        torus.neighbors_all([0, 1, 1]).filter(|&n| torus[n]).count(),
+        1
+    );
+
+    let mut hyper = NdGrid::filled([3; 4], 0u8);
+    hyper[[1, 1, 1, 1]] = 1;
+    assert_eq!(hyper.neighbors_all([1, 1, 1, 1]).count(), 80);
+    assert_eq!(hyper.iter().map(|&cell| cell as usize).sum::<usize>(), 1);
+}
@@ -0,0 +1,190 @@
+use strum::{EnumIter, IntoEnumIterator};
+
+use super::view::GridView;
+use super::{Addressing, Grid, GridError, Point};
+
+/// One of the eight rotations and reflections of a rectangle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
+pub enum Orientation {
+    Identity,
+    RotateCw,
+    Rotate180,
+    RotateCcw,
+    FlipHorizontal,
+    FlipVertical,
+    Transpose,
+    AntiTranspose,
+}
+
+impl Orientation {
+    /// Applies the orientation to a view.
+    pub fn apply<T>(self, view: GridView<'_, T>) -> GridView<'_, T> {
+        match self {
+            Orientation::Identity => view,
+            Orientation::RotateCw => view.rotate_cw(),
+            Orientation::Rotate180 => view.rotate_180(),
+            Orientation::RotateCcw => view.rotate_ccw(),
+            Orientation::FlipHorizontal => view.flip_horizontal(),
+            Orientation::FlipVertical => view.flip_vertical(),
+            Orientation::Transpose => view.transpose(),
+            Orientation::AntiTranspose => view.transpose().rotate_180(),
+        }
+    }
+}
+
+/// A match of a pattern in a grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PatternMatch {
+    /// Point of the grid under the top-left cell of the oriented pattern.
+    pub position: Point,
+    /// Orientation in which the pattern matched.
+    pub orientation: Orientation,
+}
+
+/// A rectangular pattern of cells to search in a grid. Wildcard cells match any value.
+#[derive(Debug, Clone)]
+pub struct Pattern<T> {
+    cells: Grid<Option<T>>,
+}
+
+impl<T> Pattern<T> {
+    /// Creates a Pattern from a grid of cells, where None is a wildcard.
+    pub fn new(cells: Grid<Option<T>>) -> Self {
+        Self { cells }
+    }
+
+    /// Gets the dimensions of the pattern as (columns, rows).
+    pub fn dimensions(&self) -> (usize, usize) {
+        self.cells.dimensions()
+    }
+
+    /// Checks whether the pattern matches `grid` with its top-left cell at `position`.
+    pub fn matches_at(&self, grid: &Grid<T>, position: Point) -> bool
+    where
+        T: PartialEq,
+    {
+        self.cells.points().all(|p| {
+            let expected = self.cells.get_point(p).and_then(|cell| cell.as_ref());
+            let found = grid.get_point((position.0 + p.0, position.1 + p.1));
+            match (expected, found) {
+                (None, found) => found.is_some(),
+                (Some(expected), Some(found)) => expected == found,
+                (Some(_), None) => false,
+            }
+        })
+    }
+
+    /// Gets a copy of the pattern in the given orientation.
+    pub fn oriented(&self, orientation: Orientation) -> Self
+    where
+        T: Clone,
+    {
+        let cells = orientation
+            .apply(self.cells.view())
+            .materialize()
+            .expect("Orientations of a grid are not empty");
+        Self::new(cells)
+    }
+
+    /// Gets the distinct orientations of the pattern. Orientations that produce the same cells as
+    /// an earlier one are skipped, so symmetric patterns are not matched twice.
+    pub fn orientations(&self) -> Vec<(Orientation, Self)>
+    where
+        T: Clone + PartialEq,
+    {
+        let mut orientations: Vec<(Orientation, Self)> = Vec::new();
+        for orientation in Orientation::iter() {
+            let oriented = self.oriented(orientation);
+            if orientations
+                .iter()
+                .all(|(_, other)| other.cells.block != oriented.cells.block)
+            {
+                orientations.push((orientation, oriented));
+            }
+        }
+
+        orientations
+    }
+}
+
+impl Pattern<char> {
+    /// Reads a Pattern from a string, where `wildcard` matches any character.
+    pub fn from_string(input: &str, wildcard: char) -> Result<Self, GridError> {
+        let grid = Grid::from_string(input)?;
+        let block = grid
+            .iter_rows()
+            .map(|row| {
+                row.iter()
+                    .map(|&ch| if ch == wildcard { None } else { Some(ch) })
+                    .collect()
+            })
+            .collect();
+
+        Ok(Self::new(Grid::new(block)?))
+    }
+}
+
+impl<T: PartialEq> Grid<T> {
+    /// Finds, row by row, the points where the pattern matches with its top-left cell. In bounded
+    /// grids the pattern must fit entirely inside the grid.
+    pub fn find_pattern(&self, pattern: &Pattern<T>) -> Vec<Point> {
+        let (columns, rows) = self.dimensions();
+        let (pattern_columns, pattern_rows) = pattern.dimensions();
+
+        let (last_i, last_j) = match self.addressing {
+            Addressing::Bounded => (
+                columns as isize - pattern_columns as isize,
+                rows as isize - pattern_rows as isize,
+            ),
+            Addressing::Wrapping => (columns as isize - 1, rows as isize - 1),
+        };
+
+        (0..=last_j)
+            .flat_map(|j| (0..=last_i).map(move |i| (i, j)))
+            .filter(|&p| pattern.matches_at(self, p))
+            .collect()
+    }
+
+    /// Finds the matches of the pattern in all its distinct rotations and reflections.
+    pub fn find_pattern_in_all_orientations(&self, pattern: &Pattern<T>) -> Vec<PatternMatch>
+    where
+        T: Clone,
+    {
+        pattern
+            .orientations()
+            .into_iter()
+            .flat_map(|(orientation, oriented)| {
+                self.find_pattern(&oriented)
+                    .into_iter()
+                    .map(move |position| PatternMatch {
+                        position,
+                        orientation,
+                    })
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_find_pattern() {
+    const GRID: &str = "ab.a\n\
+                        .cab\n\
+                        ba.c\n\
+                        cb..";
+
+    let grid = Grid::from_string(GRID).unwrap();
+    let pattern = Pattern::from_string("ab\n?c", '?').unwrap();
+
+    assert_eq!(grid.find_pattern(&pattern), vec![(0, 0), (2, 1)]);
+    assert_eq!(pattern.orientations().len(), 8);
+
+    let matches = grid.find_pattern_in_all_orientations(&pattern);
+    assert_eq!(matches.len(), 4);
+    assert!(matches.contains(&PatternMatch {
+        position: (0, 2),
+        orientation: Orientation::RotateCw,
+    }));
+
+    let symmetric = Pattern::from_string("a?\n?a", '?').unwrap();
+    assert_eq!(symmetric.orientations().len(), 2);
+}
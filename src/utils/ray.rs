@@ -0,0 +1,134 @@
+use super::{Direction, Grid, Point};
+
+/// Iterator over the cells of a grid visited by repeatedly stepping in one direction.
+///
+/// Yields the position and the value of every cell from the start (included) until the first
+/// position that addresses no cell, or until the ray comes back to the start, which happens in
+/// wrapping grids and for a null direction.
+#[derive(Debug)]
+pub struct Ray<'a, T> {
+    grid: &'a Grid<T>,
+    start: Option<Point>,
+    position: Option<Point>,
+    direction: Direction,
+}
+
+impl<'a, T> Iterator for Ray<'a, T> {
+    type Item = (Point, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let position = self.position?;
+        let value = self.grid.get_point(position)?;
+
+        let next = (position.0 + self.direction.0, position.1 + self.direction.1);
+        self.position = self.grid.normalize(next).filter(|&p| Some(p) != self.start);
+
+        Some((position, value))
+    }
+}
+
+impl<T> Grid<T> {
+    /// Steps from `start` in `direction` until leaving the grid or coming back to the start.
+    pub fn ray(&self, start: Point, direction: Direction) -> Ray<'_, T> {
+        let start = self.normalize(start);
+        Ray {
+            grid: self,
+            start,
+            position: start,
+            direction,
+        }
+    }
+
+    /// Steps from `start` in `direction` until leaving the grid or reaching a cell that matches
+    /// `stop`, which is not yielded.
+    pub fn ray_until<'a, F>(
+        &'a self,
+        start: Point,
+        direction: Direction,
+        stop: F,
+    ) -> impl Iterator<Item = (Point, &'a T)> + 'a
+    where
+        F: Fn(&T) -> bool + 'a,
+    {
+        self.ray(start, direction)
+            .take_while(move |(_, value)| !stop(value))
+    }
+
+    /// Iterates over the cells of row `j`, left to right.
+    pub fn row(&self, j: usize) -> Ray<'_, T> {
+        self.ray((0, j as isize), (1, 0))
+    }
+
+    /// Iterates over the cells of column `i`, top to bottom.
+    pub fn column(&self, i: usize) -> Ray<'_, T> {
+        self.ray((i as isize, 0), (0, 1))
+    }
+
+    /// Iterates over all rows, top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = Ray<'_, T>> {
+        (0..self.dimensions().1).map(|j| self.row(j))
+    }
+
+    /// Iterates over all columns, left to right.
+    pub fn columns(&self) -> impl Iterator<Item = Ray<'_, T>> {
+        (0..self.dimensions().0).map(|i| self.column(i))
+    }
+
+    /// Iterates over all diagonals going down and to the right, starting from the bottom-left
+    /// corner and ending at the top-right corner.
+    pub fn diagonals(&self) -> impl Iterator<Item = Ray<'_, T>> {
+        let (columns, rows) = self.dimensions();
+        let left = (0..rows).rev().map(|j| (0, j as isize));
+        let top = (1..columns).map(|i| (i as isize, 0));
+        left.chain(top).map(|start| self.ray(start, (1, 1)))
+    }
+
+    /// Iterates over all anti-diagonals going down and to the left, starting from the top-left
+    /// corner and ending at the bottom-right corner.
+    pub fn anti_diagonals(&self) -> impl Iterator<Item = Ray<'_, T>> {
+        let (columns, rows) = self.dimensions();
+        let last_column = columns as isize - 1;
+        let top = (0..columns).map(|i| (i as isize, 0));
+        let right = (1..rows).map(move |j| (last_column, j as isize));
+        top.chain(right).map(|start| self.ray(start, (-1, 1)))
+    }
+}
+
+#[test]
+fn test_rays() {
+    const GRID: &str = "abc\n\
+                        def\n\
+                        ghi";
+
+    let grid = Grid::from_string(GRID).unwrap();
+    let collect = |ray: Ray<char>| ray.map(|(_, ch)| *ch).collect::<String>();
+
+    assert_eq!(collect(grid.ray((0, 0), (1, 1))), "aei");
+    assert_eq!(collect(grid.ray((2, 1), (-1, 0))), "fed");
+    assert_eq!(collect(grid.ray((3, 0), (1, 0))), "");
+    assert_eq!(collect(grid.ray((1, 1), (0, 0))), "e");
+    assert_eq!(
+        grid.ray_until((0, 1), (1, 0), |ch| *ch == 'f')
+            .map(|(p, _)| p)
+            .collect::<Vec<_>>(),
+        vec![(0, 1), (1, 1)]
+    );
+
+    assert_eq!(collect(grid.row(1)), "def");
+    assert_eq!(collect(grid.column(2)), "cfi");
+    assert_eq!(grid.rows().count(), 3);
+    assert_eq!(grid.columns().map(collect).collect::<Vec<_>>()[0], "adg");
+    assert_eq!(
+        grid.diagonals().map(collect).collect::<Vec<_>>(),
+        vec!["g", "dh", "aei", "bf", "c"]
+    );
+    assert_eq!(
+        grid.anti_diagonals().map(collect).collect::<Vec<_>>(),
+        vec!["a", "bd", "ceg", "fh", "i"]
+    );
+
+    let wrapping = grid.with_addressing(super::Addressing::Wrapping);
+    assert_eq!(collect(wrapping.ray((1, 0), (-1, 0))), "bac");
+    assert_eq!(collect(wrapping.ray((-1, 0), (1, 1))), "cdh");
+    assert_eq!(collect(wrapping.ray((0, 0), (2, 0))), "acb");
+}
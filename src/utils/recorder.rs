@@ -0,0 +1,211 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use super::image::image_from_styles;
+use super::render::{render_styles, Renderer, Style};
+use super::Grid;
+
+/// Records the successive states of a simulation as frames of an animation.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    frames: Vec<Grid<Style>>,
+    frame_rate: f64,
+    every: usize,
+    steps: usize,
+}
+
+impl Recorder {
+    /// Creates an empty Recorder that plays `frame_rate` frames per second. The frame rate must
+    /// be positive and finite.
+    pub fn new(frame_rate: f64) -> Result<Self, Box<dyn std::error::Error>> {
+        if !(frame_rate.is_finite() && frame_rate > 0.0) {
+            return Err(format!("Invalid frame rate: {}", frame_rate).into());
+        }
+
+        Ok(Self {
+            frames: Vec::new(),
+            frame_rate,
+            every: 1,
+            steps: 0,
+        })
+    }
+
+    /// Keeps only one step out of every `every`, starting with the first one.
+    pub fn every(mut self, every: usize) -> Self {
+        self.every = every.max(1);
+        self
+    }
+
+    /// Number of recorded frames.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Captures the current state drawn by `renderer` as a step of the simulation.
+    pub fn record<T: PartialEq>(&mut self, renderer: &Renderer<'_, T>) {
+        if self.steps.is_multiple_of(self.every) {
+            self.frames.push(renderer.styles());
+        }
+        self.steps += 1;
+    }
+
+    // Largest frame dimensions as (columns, rows).
+    fn dimensions(&self) -> (usize, usize) {
+        self.frames
+            .iter()
+            .map(|frame| frame.dimensions())
+            .fold((0, 0), |(c, r), (fc, fr)| (c.max(fc), r.max(fr)))
+    }
+
+    /// Writes the frames as an asciinema v2 cast file.
+    pub fn write_cast<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        let (columns, rows) = self.dimensions();
+        writeln!(
+            writer,
+            "{{\"version\": 2, \"width\": {}, \"height\": {}}}",
+            columns, rows
+        )?;
+
+        for (k, frame) in self.frames.iter().enumerate() {
+            let time = k as f64 / self.frame_rate;
+            let screen = format!(
+                "\x1b[2J\x1b[H{}",
+                render_styles(frame).replace('\n', "\r\n")
+            );
+            writeln!(writer, "[{:.6}, \"o\", \"{}\"]", time, escape_json(&screen))?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the frames as a looping animated GIF with `scale` x `scale` pixels per cell.
+    pub fn write_gif<W: Write>(
+        &self,
+        writer: W,
+        scale: usize,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (columns, rows) = self.dimensions();
+        let (width, height) = (gif_length(columns, scale)?, gif_length(rows, scale)?);
+
+        // GIF delays are counted in hundredths of a second
+        let delay = (100.0 / self.frame_rate).round();
+        if delay > u16::MAX as f64 {
+            return Err(format!("Frame rate too low for a GIF: {}", self.frame_rate).into());
+        }
+        let delay = delay as u16;
+
+        let mut encoder = gif::Encoder::new(writer, width, height, &[])?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+
+        for frame in &self.frames {
+            let image = image_from_styles(frame, scale);
+            let (frame_width, frame_height) = image.dimensions();
+            let mut gif_frame = gif::Frame::from_rgb(
+                gif_length(frame_width, 1)?,
+                gif_length(frame_height, 1)?,
+                &image.rgb_bytes(),
+            );
+            gif_frame.delay = delay;
+            encoder.write_frame(&gif_frame)?;
+        }
+
+        Ok(())
+    }
+
+    /// Saves the frames to a file, as a GIF or a cast file depending on its extension.
+    pub fn save<P: AsRef<Path>>(
+        &self,
+        path: P,
+        scale: usize,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("cast") => {
+                let mut writer = BufWriter::new(File::create(path)?);
+                self.write_cast(&mut writer)?;
+                writer.flush()?;
+                Ok(())
+            }
+            Some("gif") => self.write_gif(BufWriter::new(File::create(path)?), scale),
+            _ => Err(format!("Unsupported animation format: {}", path.display()).into()),
+        }
+    }
+}
+
+// Length in pixels of `cells` cells of `scale` pixels, which must fit in a GIF dimension.
+fn gif_length(cells: usize, scale: usize) -> Result<u16, Box<dyn std::error::Error>> {
+    cells
+        .checked_mul(scale)
+        .and_then(|length| u16::try_from(length).ok())
+        .ok_or_else(|| {
+            format!(
+                "Animation too large for a GIF: {} cells of {} pixels",
+                cells, scale
+            )
+            .into()
+        })
+}
+
+fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => escaped.push(ch),
+        }
+    }
+
+    escaped
+}
+
+#[test]
+fn test_recorder() {
+    use super::render::Color;
+
+    let mut grid = Grid::from_string("...\n...").unwrap();
+    assert!(Recorder::new(0.0).is_err());
+    assert!(Recorder::new(-1.0).is_err());
+    assert!(Recorder::new(f64::NAN).is_err());
+    assert!(Recorder::new(f64::INFINITY).is_err());
+
+    let mut recorder = Recorder::new(10.0).unwrap().every(2);
+
+    for i in 0..5 {
+        grid.set(i % 3, i / 3, '#');
+        recorder.record(&Renderer::new(&grid).color('#', Color::RED));
+    }
+    assert_eq!(recorder.len(), 3);
+
+    let mut cast = Vec::new();
+    recorder.write_cast(&mut cast).unwrap();
+    let cast = String::from_utf8(cast).unwrap();
+    let lines: Vec<&str> = cast.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0], "{\"version\": 2, \"width\": 3, \"height\": 2}");
+    assert!(lines[2].starts_with("[0.100000, \"o\", \"\\u001b[2J\\u001b[H"));
+    assert!(lines[3].contains("\\r\\n"));
+
+    let mut gif = Vec::new();
+    recorder.write_gif(&mut gif, 2).unwrap();
+    assert!(gif.starts_with(b"GIF89a"));
+    assert!(recorder.write_gif(Vec::new(), 30_000).is_err());
+    assert!(Recorder::new(0.001)
+        .unwrap()
+        .write_gif(Vec::new(), 1)
+        .is_err());
+}
@@ -0,0 +1,241 @@
+use std::collections::{HashSet, VecDeque};
+
+use super::{Direction, Grid, Point, DIRECTIONS_4};
+
+/// A connected group of cells in a grid.
+#[derive(Debug, Clone)]
+pub struct Region {
+    /// Label of the region in the label grid.
+    pub label: usize,
+    /// Cells of the region, in discovery order.
+    pub cells: Vec<Point>,
+    /// Number of cell edges on the boundary of the region.
+    pub perimeter: usize,
+    /// Number of straight segments on the boundary of the region.
+    pub sides: usize,
+}
+
+impl Region {
+    /// Number of cells in the region.
+    pub fn area(&self) -> usize {
+        self.cells.len()
+    }
+}
+
+/// The connected components of a grid.
+#[derive(Debug, Clone)]
+pub struct Regions {
+    /// Label of the region every cell belongs to.
+    pub labels: Grid<usize>,
+    /// All regions, indexed by label.
+    pub regions: Vec<Region>,
+}
+
+impl<T> Grid<T> {
+    /// Collects the cells reachable from `seed` through orthogonal steps between cells for
+    /// which `same_region(current, neighbor)` holds. The seed is always the first cell.
+    pub fn flood_fill<F>(&self, seed: Point, same_region: F) -> Vec<Point>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        let mut visited = self.mask();
+        self.flood_fill_from(seed, &same_region, &mut visited)
+    }
+
+    /// Splits the grid into connected regions. Two orthogonally adjacent cells belong to the
+    /// same region when `same_region(current, neighbor)` holds.
+    pub fn label_regions<F>(&self, same_region: F) -> Regions
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        let mut visited = self.mask();
+        let mut labels = self.filled_like(0);
+        let mut regions = Vec::new();
+
+        for p in self.points() {
+            if visited.get_point(p) != Some(&false) {
+                continue;
+            }
+
+            let label = regions.len();
+            let cells = self.flood_fill_from(p, &same_region, &mut visited);
+            for &(i, j) in &cells {
+                labels.set(i as usize, j as usize, label);
+            }
+
+            regions.push(Region {
+                label,
+                cells,
+                perimeter: 0,
+                sides: 0,
+            });
+        }
+
+        for region in regions.iter_mut() {
+            region.perimeter = count_perimeter(&labels, region);
+            region.sides = count_sides(&labels, region);
+        }
+
+        Regions { labels, regions }
+    }
+
+    // An all-false grid with the same shape and addressing.
+    fn mask(&self) -> Grid<bool> {
+        self.filled_like(false)
+    }
+
+    fn flood_fill_from<F>(
+        &self,
+        seed: Point,
+        same_region: &F,
+        visited: &mut Grid<bool>,
+    ) -> Vec<Point>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        let mut cells = Vec::new();
+        let Some(seed) = self.normalize(seed) else {
+            return cells;
+        };
+
+        let mut queue = VecDeque::new();
+        visited.set(seed.0 as usize, seed.1 as usize, true);
+        queue.push_back(seed);
+
+        while let Some(p) = queue.pop_front() {
+            cells.push(p);
+            let value = self
+                .get_point(p)
+                .expect("Queued points are inside the grid");
+
+            for n in self.neighbors(p) {
+                if visited.get_point(n) != Some(&false) {
+                    continue;
+                }
+
+                if let Some(neighbor) = self.get_point(n) {
+                    if same_region(value, neighbor) {
+                        visited.set(n.0 as usize, n.1 as usize, true);
+                        queue.push_back(n);
+                    }
+                }
+            }
+        }
+
+        cells
+    }
+}
+
+fn count_perimeter(labels: &Grid<usize>, region: &Region) -> usize {
+    let in_region = |p: Point| labels.get_point(p) == Some(&region.label);
+
+    region
+        .cells
+        .iter()
+        .map(|p| {
+            DIRECTIONS_4
+                .iter()
+                .filter(|d| !in_region((p.0 + d.0, p.1 + d.1)))
+                .count()
+        })
+        .sum()
+}
+
+// Merges the fence segments of the region into straight sides. A fence segment is a cell edge
+// facing out of the region, and it continues along the edge while the next cell faces out the
+// same way. Walking the runs instead of counting corners also finds the sides that wrap all the
+// way around a torus, which have no corners.
+fn count_sides(labels: &Grid<usize>, region: &Region) -> usize {
+    let in_region = |p: Point| labels.get_point(p) == Some(&region.label);
+
+    let fences: Vec<(Point, Direction)> = region
+        .cells
+        .iter()
+        .flat_map(|&p| DIRECTIONS_4.iter().map(move |&d| (p, d)))
+        .filter(|&(p, d)| !in_region((p.0 + d.0, p.1 + d.1)))
+        .collect();
+    let mut remaining: HashSet<(Point, Direction)> = fences.iter().copied().collect();
+
+    let mut sides = 0;
+    for (p, d) in fences {
+        if !remaining.remove(&(p, d)) {
+            continue;
+        }
+        sides += 1;
+
+        // Both ways along the edge, up to the ends of the side or around back to `p`
+        for step in [(-d.1, d.0), (d.1, -d.0)] {
+            let mut q = p;
+            while let Some(next) = labels.normalize((q.0 + step.0, q.1 + step.1)) {
+                if !remaining.remove(&(next, d)) {
+                    break;
+                }
+                q = next;
+            }
+        }
+    }
+
+    sides
+}
+
+#[test]
+fn test_label_regions() {
+    const GRID: &str = "AAAA\n\
+                        BBCD\n\
+                        BBCC\n\
+                        EEEC";
+
+    let grid = Grid::from_string(GRID).unwrap();
+    let regions = grid.label_regions(|a, b| a == b);
+
+    let summary: Vec<(usize, usize, usize)> = regions
+        .regions
+        .iter()
+        .map(|r| (r.area(), r.perimeter, r.sides))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![(4, 10, 4), (4, 8, 4), (4, 10, 8), (1, 4, 4), (3, 8, 4)]
+    );
+
+    assert_eq!(regions.labels.get(3, 1), Some(3));
+    assert_eq!(grid.flood_fill((2, 1), |a, b| a == b).len(), 4);
+
+    // Seeds outside of a wrapping grid resolve to the cell they address
+    let torus = grid.clone().with_addressing(super::Addressing::Wrapping);
+    let mut row = torus.flood_fill((-1, 0), |a, b| a == b);
+    row.sort();
+    assert_eq!(row, vec![(0, 0), (1, 0), (2, 0), (3, 0)]);
+    assert!(torus.flood_fill((6, -3), |a, b| a == b).contains(&(3, 3)));
+    assert!(grid.flood_fill((-1, 0), |a, b| a == b).is_empty());
+
+    // Bands around a torus have straight sides without corners
+    let bands = Grid::from_string("AAAA\nBBBB\nBBBB")
+        .unwrap()
+        .with_addressing(super::Addressing::Wrapping);
+    let summary: Vec<(usize, usize, usize)> = bands
+        .label_regions(|a, b| a == b)
+        .regions
+        .iter()
+        .map(|r| (r.area(), r.perimeter, r.sides))
+        .collect();
+    assert_eq!(summary, vec![(4, 8, 2), (8, 8, 2)]);
+
+    const HOLES: &str = "AAAAAA\n\
+                         AAABBA\n\
+                         AAABBA\n\
+                         ABBAAA\n\
+                         ABBAAA\n\
+                         AAAAAA";
+    let holes = Grid::from_string(HOLES).unwrap();
+    let regions = holes.label_regions(|a, b| a == b);
+    assert_eq!(regions.regions[0].sides, 12);
+    assert_eq!(
+        regions
+            .regions
+            .iter()
+            .map(|r| r.area() * r.sides)
+            .sum::<usize>(),
+        368
+    );
+}
@@ -0,0 +1,298 @@
+use std::fmt::Write;
+
+use super::{Grid, Point};
+
+/// A 24-bit RGB color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color(pub u8, pub u8, pub u8);
+
+impl Color {
+    pub const BLACK: Color = Color(0, 0, 0);
+    pub const WHITE: Color = Color(255, 255, 255);
+    pub const GRAY: Color = Color(128, 128, 128);
+    pub const RED: Color = Color(220, 50, 47);
+    pub const GREEN: Color = Color(133, 153, 0);
+    pub const BLUE: Color = Color(38, 139, 210);
+    pub const YELLOW: Color = Color(181, 137, 0);
+
+    /// Blends linearly from `self` (t = 0) to `other` (t = 1).
+    pub fn lerp(self, other: Color, t: f64) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        Color(
+            mix(self.0, other.0),
+            mix(self.1, other.1),
+            mix(self.2, other.2),
+        )
+    }
+}
+
+/// A layer drawn on top of the grid cells.
+#[derive(Debug, Clone)]
+pub enum Overlay {
+    /// Paints the background of the points.
+    Highlight { points: Vec<Point>, color: Color },
+    /// Draws the points as a path of arrows pointing to the next point.
+    Path { points: Vec<Point>, color: Color },
+    /// Paints the background of the points from blue (lowest) to red (highest value).
+    Heatmap { values: Vec<(Point, f64)> },
+}
+
+/// How a single cell is drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Style {
+    pub glyph: char,
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+}
+
+const HEATMAP_COLD: Color = Color(0, 0, 255);
+const HEATMAP_HOT: Color = Color(255, 0, 0);
+
+/// Draws a Grid with colors and overlays.
+pub struct Renderer<'a, T> {
+    grid: &'a Grid<T>,
+    glyph: Box<dyn Fn(&T) -> char + 'a>,
+    palette: Vec<(T, Color)>,
+    overlays: Vec<Overlay>,
+}
+
+impl<'a> Renderer<'a, char> {
+    /// Creates a Renderer that draws every cell as its own character.
+    pub fn new(grid: &'a Grid) -> Self {
+        Self::with_glyphs(grid, |ch| *ch)
+    }
+}
+
+impl<'a, T> Renderer<'a, T> {
+    /// Creates a Renderer that draws every cell as the character returned by `glyph`.
+    pub fn with_glyphs<F>(grid: &'a Grid<T>, glyph: F) -> Self
+    where
+        F: Fn(&T) -> char + 'a,
+    {
+        Self {
+            grid,
+            glyph: Box::new(glyph),
+            palette: Vec::new(),
+            overlays: Vec::new(),
+        }
+    }
+
+    /// Draws the cells equal to `value` in `color`.
+    pub fn color(mut self, value: T, color: Color) -> Self {
+        self.palette.push((value, color));
+        self
+    }
+
+    /// Adds a layer on top of the previous ones.
+    pub fn overlay(mut self, overlay: Overlay) -> Self {
+        self.overlays.push(overlay);
+        self
+    }
+
+    /// Paints the background of `points` in `color`.
+    pub fn highlight<I>(self, points: I, color: Color) -> Self
+    where
+        I: IntoIterator<Item = Point>,
+    {
+        self.overlay(Overlay::Highlight {
+            points: points.into_iter().collect(),
+            color,
+        })
+    }
+
+    /// Draws `points` as a path of arrows in `color`.
+    pub fn path<I>(self, points: I, color: Color) -> Self
+    where
+        I: IntoIterator<Item = Point>,
+    {
+        self.overlay(Overlay::Path {
+            points: points.into_iter().collect(),
+            color,
+        })
+    }
+
+    /// Paints the background of the points according to their value.
+    pub fn heatmap<I>(self, values: I) -> Self
+    where
+        I: IntoIterator<Item = (Point, f64)>,
+    {
+        self.overlay(Overlay::Heatmap {
+            values: values.into_iter().collect(),
+        })
+    }
+
+    /// Resolves how every cell is drawn, after applying the palette and all the overlays.
+    pub fn styles(&self) -> Grid<Style>
+    where
+        T: PartialEq,
+    {
+        let block = self
+            .grid
+            .iter_rows()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| Style {
+                        glyph: (self.glyph)(cell),
+                        foreground: self
+                            .palette
+                            .iter()
+                            .find(|(value, _)| value == cell)
+                            .map(|(_, color)| *color),
+                        background: None,
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut styles = Grid::from_block(block);
+
+        for overlay in &self.overlays {
+            apply_overlay(&mut styles, overlay);
+        }
+
+        styles
+    }
+
+    /// Draws the grid as text with ANSI color escape sequences.
+    pub fn render(&self) -> String
+    where
+        T: PartialEq,
+    {
+        render_styles(&self.styles())
+    }
+
+    /// Prints the rendered grid to the standard output.
+    pub fn print(&self)
+    where
+        T: PartialEq,
+    {
+        println!("{}", self.render());
+    }
+}
+
+/// Draws resolved cell styles as text with ANSI color escape sequences.
+pub fn render_styles(styles: &Grid<Style>) -> String {
+    let mut output = String::new();
+
+    for (j, row) in styles.iter_rows_enumerate() {
+        if j > 0 {
+            output.push('\n');
+        }
+
+        for style in row {
+            let styled = style.foreground.is_some() || style.background.is_some();
+            if let Some(Color(r, g, b)) = style.foreground {
+                write!(output, "\x1b[38;2;{};{};{}m", r, g, b).unwrap();
+            }
+            if let Some(Color(r, g, b)) = style.background {
+                write!(output, "\x1b[48;2;{};{};{}m", r, g, b).unwrap();
+            }
+
+            output.push(style.glyph);
+
+            if styled {
+                output.push_str("\x1b[0m");
+            }
+        }
+    }
+
+    output
+}
+
+fn apply_overlay(styles: &mut Grid<Style>, overlay: &Overlay) {
+    let mut update = |p: Point, f: &dyn Fn(&mut Style)| {
+        if let Some((i, j)) = styles.normalize(p) {
+            f(&mut styles.block[j as usize][i as usize]);
+        }
+    };
+
+    match overlay {
+        Overlay::Highlight { points, color } => {
+            for &p in points {
+                update(p, &|style| style.background = Some(*color));
+            }
+        }
+        Overlay::Path { points, color } => {
+            for (k, &p) in points.iter().enumerate() {
+                // The last point keeps pointing in the direction it was reached from
+                let step = match (points.get(k + 1), k.checked_sub(1)) {
+                    (Some(next), _) => (next.0 - p.0, next.1 - p.1),
+                    (None, Some(previous)) => (p.0 - points[previous].0, p.1 - points[previous].1),
+                    (None, None) => (0, 0),
+                };
+
+                update(p, &|style| {
+                    if let Some(arrow) = arrow(step) {
+                        style.glyph = arrow;
+                    }
+                    style.foreground = Some(*color);
+                });
+            }
+        }
+        Overlay::Heatmap { values } => {
+            let min = values.iter().map(|(_, v)| *v).fold(f64::INFINITY, f64::min);
+            let max = values
+                .iter()
+                .map(|(_, v)| *v)
+                .fold(f64::NEG_INFINITY, f64::max);
+            let range = if max > min { max - min } else { 1.0 };
+
+            for &(p, value) in values {
+                let color = HEATMAP_COLD.lerp(HEATMAP_HOT, (value - min) / range);
+                update(p, &|style| style.background = Some(color));
+            }
+        }
+    }
+}
+
+// Arrow pointing in the direction of a step, if the step is not null.
+fn arrow(step: Point) -> Option<char> {
+    match (step.0.signum(), step.1.signum()) {
+        (0, -1) => Some('^'),
+        (1, 0) => Some('>'),
+        (0, 1) => Some('v'),
+        (-1, 0) => Some('<'),
+        (1, -1) | (-1, 1) => Some('/'),
+        (1, 1) | (-1, -1) => Some('\\'),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_render() {
+    const GRID: &str = "#..\n\
+                        ...";
+
+    let grid = Grid::from_string(GRID).unwrap();
+
+    let plain = Renderer::new(&grid).render();
+    assert_eq!(plain, GRID);
+
+    let renderer = Renderer::new(&grid)
+        .color('#', Color::RED)
+        .path([(0, 1), (1, 1), (1, 0)], Color::GREEN)
+        .highlight([(2, 0)], Color::BLUE);
+
+    let glyphs: Vec<String> = renderer
+        .styles()
+        .iter_rows()
+        .map(|row| row.iter().map(|style| style.glyph).collect())
+        .collect();
+    assert_eq!(glyphs, vec!["#^.", ">^."]);
+
+    let rendered = renderer.render();
+    assert!(rendered.starts_with("\x1b[38;2;220;50;47m#\x1b[0m"));
+    assert!(rendered.contains("\x1b[48;2;38;139;210m.\x1b[0m"));
+
+    let heat = Renderer::new(&grid)
+        .heatmap([((0, 0), 1.0), ((1, 0), 3.0)])
+        .styles();
+    assert_eq!(
+        heat.get_point((0, 0)).unwrap().background,
+        Some(HEATMAP_COLD)
+    );
+    assert_eq!(
+        heat.get_point((1, 0)).unwrap().background,
+        Some(HEATMAP_HOT)
+    );
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use super::{Grid, Point};
+
+impl<T> Grid<T> {
+    /// Finds the first point, row by row, whose cell equals `value`.
+    pub fn find(&self, value: &T) -> Option<Point>
+    where
+        T: PartialEq,
+    {
+        self.find_by(|cell| cell == value)
+    }
+
+    /// Finds the first point, row by row, whose cell matches `predicate`.
+    pub fn find_by<F>(&self, predicate: F) -> Option<Point>
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.find_all_by(predicate).next()
+    }
+
+    /// Iterates, row by row, over all points whose cell equals `value`.
+    pub fn find_all<'a>(&'a self, value: &'a T) -> impl Iterator<Item = Point> + 'a
+    where
+        T: PartialEq,
+    {
+        self.find_all_by(move |cell| cell == value)
+    }
+
+    /// Iterates, row by row, over all points whose cell matches `predicate`.
+    pub fn find_all_by<'a, F>(&'a self, predicate: F) -> impl Iterator<Item = Point> + 'a
+    where
+        F: Fn(&T) -> bool + 'a,
+    {
+        self.points()
+            .filter(move |&p| self.get_point(p).is_some_and(&predicate))
+    }
+
+    /// Groups all points by the value of their cell. Points of each group are sorted row by row.
+    pub fn group_positions(&self) -> HashMap<T, Vec<Point>>
+    where
+        T: Eq + Hash + Clone,
+    {
+        self.group_positions_by(|_| true)
+    }
+
+    /// Groups the points whose cell matches `predicate` by the value of their cell. Points of each
+    /// group are sorted row by row.
+    pub fn group_positions_by<F>(&self, predicate: F) -> HashMap<T, Vec<Point>>
+    where
+        T: Eq + Hash + Clone,
+        F: Fn(&T) -> bool,
+    {
+        let mut groups: HashMap<T, Vec<Point>> = HashMap::new();
+
+        for (j, row) in self.iter_rows_enumerate() {
+            for (i, cell) in row.iter().enumerate() {
+                if predicate(cell) {
+                    groups
+                        .entry(cell.clone())
+                        .or_default()
+                        .push((i as isize, j as isize));
+                }
+            }
+        }
+
+        groups
+    }
+}
+
+#[test]
+fn test_search() {
+    const GRID: &str = "a.b\n\
+                        .ba\n\
+                        b..";
+
+    let grid = Grid::from_string(GRID).unwrap();
+
+    assert_eq!(grid.find(&'b'), Some((2, 0)));
+    assert_eq!(grid.find(&'c'), None);
+    assert_eq!(grid.find_by(|ch| *ch != '.'), Some((0, 0)));
+    assert_eq!(
+        grid.find_all(&'b').collect::<Vec<_>>(),
+        vec![(2, 0), (1, 1), (0, 2)]
+    );
+
+    let groups = grid.group_positions_by(|ch| ch.is_alphanumeric());
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[&'a'], vec![(0, 0), (2, 1)]);
+    assert_eq!(grid.group_positions()[&'.'].len(), 4);
+}
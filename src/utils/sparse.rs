@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use super::{Grid, Point, DIRECTIONS_4};
+
+/// An unbounded grid that only stores the cells that have been set.
+///
+/// Cells that were never set read as the default value. Coordinates can be negative.
+#[derive(Debug, Clone)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point, T>,
+    default: T,
+}
+
+impl<T> SparseGrid<T> {
+    /// Creates an empty SparseGrid where every cell reads as `default`.
+    pub fn new(default: T) -> Self {
+        Self {
+            cells: HashMap::new(),
+            default,
+        }
+    }
+
+    /// Builds a SparseGrid from a dense Grid, storing only the cells that differ from `default`.
+    /// Cell (i, j) of the Grid is placed at point (i, j).
+    pub fn from_grid(grid: &Grid<T>, default: T) -> Self
+    where
+        T: Clone + PartialEq,
+    {
+        let mut sparse = Self::new(default);
+        for p in grid.points() {
+            if let Some(value) = grid.get_point(p) {
+                if *value != sparse.default {
+                    sparse.cells.insert(p, value.clone());
+                }
+            }
+        }
+
+        sparse
+    }
+
+    /// Reads the cell at (i, j), where `i` is the column and `j` is the row.
+    pub fn get(&self, i: isize, j: isize) -> T
+    where
+        T: Copy,
+    {
+        *self.get_point((i, j))
+    }
+
+    /// Reads a reference to the cell at point `p`.
+    pub fn get_point(&self, p: Point) -> &T {
+        self.cells.get(&p).unwrap_or(&self.default)
+    }
+
+    /// Writes the cell at (i, j), where `i` is the column and `j` is the row.
+    pub fn set(&mut self, i: isize, j: isize, value: T) {
+        self.set_point((i, j), value);
+    }
+
+    /// Writes the cell at point `p`.
+    pub fn set_point(&mut self, p: Point, value: T) {
+        self.cells.insert(p, value);
+    }
+
+    /// Resets the cell at point `p` to the default value, returning the previous value if it was
+    /// set.
+    pub fn remove(&mut self, p: Point) -> Option<T> {
+        self.cells.remove(&p)
+    }
+
+    /// Checks whether the cell at point `p` has been set.
+    pub fn contains(&self, p: Point) -> bool {
+        self.cells.contains_key(&p)
+    }
+
+    /// Number of cells that have been set.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Iterates over the points that have been set, in no particular order.
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        self.cells.keys().copied()
+    }
+
+    /// Iterates over the cells that have been set, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells.iter().map(|(p, value)| (*p, value))
+    }
+
+    /// Iterates over the orthogonal neighbors of `p`.
+    pub fn neighbors(&self, p: Point) -> impl Iterator<Item = Point> {
+        DIRECTIONS_4.iter().map(move |d| (p.0 + d.0, p.1 + d.1))
+    }
+
+    /// Gets the smallest rectangle containing every set cell as its (top-left, bottom-right)
+    /// corners, both included.
+    pub fn bounding_box(&self) -> Option<(Point, Point)> {
+        let mut points = self.points();
+        let first = points.next()?;
+
+        Some(points.fold((first, first), |(min, max), p| {
+            (
+                (min.0.min(p.0), min.1.min(p.1)),
+                (max.0.max(p.0), max.1.max(p.1)),
+            )
+        }))
+    }
+
+    /// Gets the dimensions of the bounding box as (columns, rows).
+    pub fn dimensions(&self) -> (usize, usize) {
+        self.bounding_box().map_or((0, 0), |(min, max)| {
+            ((max.0 - min.0 + 1) as usize, (max.1 - min.1 + 1) as usize)
+        })
+    }
+
+    /// Copies the bounding box into a dense Grid. Returns the Grid together with the point that
+    /// cell (0, 0) of the Grid corresponds to, or None if no cell is stored.
+    pub fn to_grid(&self) -> Option<(Grid<T>, Point)>
+    where
+        T: Clone,
+    {
+        let (min, max) = self.bounding_box()?;
+
+        let block = (min.1..=max.1)
+            .map(|j| {
+                (min.0..=max.0)
+                    .map(|i| self.get_point((i, j)).clone())
+                    .collect()
+            })
+            .collect();
+
+        Some((Grid::from_block(block), min))
+    }
+}
+
+impl<T> Grid<T> {
+    /// Converts the Grid into a SparseGrid, storing only the cells that differ from `default`.
+    pub fn to_sparse(&self, default: T) -> SparseGrid<T>
+    where
+        T: Clone + PartialEq,
+    {
+        SparseGrid::from_grid(self, default)
+    }
+}
+
+#[test]
+fn test_sparse_grid() {
+    let mut sparse = SparseGrid::new('.');
+    sparse.set(-2, 1, '#');
+    sparse.set(1, -1, '#');
+
+    assert_eq!(sparse.get(-2, 1), '#');
+    assert_eq!(sparse.get(100, -100), '.');
+    assert_eq!(sparse.bounding_box(), Some(((-2, -1), (1, 1))));
+    assert_eq!(sparse.dimensions(), (4, 3));
+
+    let (grid, origin) = sparse.to_grid().unwrap();
+    assert_eq!(origin, (-2, -1));
+    assert_eq!(grid.to_string(), "...#\n....\n#...");
+
+    let round_trip = grid.to_sparse('.');
+    assert_eq!(round_trip.len(), 2);
+    assert!(round_trip.contains((3, 0)));
+
+    assert_eq!(sparse.remove((1, -1)), Some('#'));
+    assert_eq!(sparse.dimensions(), (1, 1));
+    assert_eq!(SparseGrid::<char>::new('.').bounding_box(), None);
+    assert_eq!(SparseGrid::<char>::new('.').to_grid(), None);
+}
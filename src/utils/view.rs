@@ -0,0 +1,232 @@
+use super::{Direction, Grid, GridError, Point};
+
+/// A read-only window into a Grid, possibly rotated, flipped or transposed.
+///
+/// Cell (i, j) of the view maps to `origin + i * column_step + j * row_step` in the grid, so
+/// views can be chained without copying the underlying cells.
+#[derive(Debug)]
+pub struct GridView<'a, T> {
+    grid: &'a Grid<T>,
+    origin: Point,
+    column_step: Direction,
+    row_step: Direction,
+    columns: usize,
+    rows: usize,
+}
+
+impl<T> Clone for GridView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for GridView<'_, T> {}
+
+impl<T> Grid<T> {
+    /// Views the whole grid without any transformation.
+    pub fn view(&self) -> GridView<'_, T> {
+        let (columns, rows) = self.dimensions();
+        GridView {
+            grid: self,
+            origin: (0, 0),
+            column_step: (1, 0),
+            row_step: (0, 1),
+            columns,
+            rows,
+        }
+    }
+
+    /// Views the grid with rows and columns swapped.
+    pub fn transpose(&self) -> GridView<'_, T> {
+        self.view().transpose()
+    }
+
+    /// Views the grid rotated 90° clockwise.
+    pub fn rotate_cw(&self) -> GridView<'_, T> {
+        self.view().rotate_cw()
+    }
+
+    /// Views the grid rotated 90° counterclockwise.
+    pub fn rotate_ccw(&self) -> GridView<'_, T> {
+        self.view().rotate_ccw()
+    }
+
+    /// Views the grid rotated 180°.
+    pub fn rotate_180(&self) -> GridView<'_, T> {
+        self.view().rotate_180()
+    }
+
+    /// Views the grid mirrored left to right.
+    pub fn flip_horizontal(&self) -> GridView<'_, T> {
+        self.view().flip_horizontal()
+    }
+
+    /// Views the grid mirrored top to bottom.
+    pub fn flip_vertical(&self) -> GridView<'_, T> {
+        self.view().flip_vertical()
+    }
+
+    /// Views the rectangle of `columns` x `rows` cells starting at (i, j).
+    pub fn window(&self, i: usize, j: usize, columns: usize, rows: usize) -> GridView<'_, T> {
+        self.view().window(i, j, columns, rows)
+    }
+}
+
+impl<'a, T> GridView<'a, T> {
+    /// Maps a point of the view to the corresponding point of the grid.
+    fn source_point(&self, i: isize, j: isize) -> Point {
+        (
+            self.origin.0 + i * self.column_step.0 + j * self.row_step.0,
+            self.origin.1 + i * self.column_step.1 + j * self.row_step.1,
+        )
+    }
+
+    fn last_column(&self) -> isize {
+        self.columns.saturating_sub(1) as isize
+    }
+
+    fn last_row(&self) -> isize {
+        self.rows.saturating_sub(1) as isize
+    }
+
+    /// Swaps rows and columns.
+    pub fn transpose(self) -> Self {
+        Self {
+            column_step: self.row_step,
+            row_step: self.column_step,
+            columns: self.rows,
+            rows: self.columns,
+            ..self
+        }
+    }
+
+    /// Rotates the view 90° clockwise.
+    pub fn rotate_cw(self) -> Self {
+        Self {
+            origin: self.source_point(0, self.last_row()),
+            column_step: (-self.row_step.0, -self.row_step.1),
+            row_step: self.column_step,
+            columns: self.rows,
+            rows: self.columns,
+            ..self
+        }
+    }
+
+    /// Rotates the view 90° counterclockwise.
+    pub fn rotate_ccw(self) -> Self {
+        Self {
+            origin: self.source_point(self.last_column(), 0),
+            column_step: self.row_step,
+            row_step: (-self.column_step.0, -self.column_step.1),
+            columns: self.rows,
+            rows: self.columns,
+            ..self
+        }
+    }
+
+    /// Rotates the view 180°.
+    pub fn rotate_180(self) -> Self {
+        self.flip_horizontal().flip_vertical()
+    }
+
+    /// Mirrors the view left to right.
+    pub fn flip_horizontal(self) -> Self {
+        Self {
+            origin: self.source_point(self.last_column(), 0),
+            column_step: (-self.column_step.0, -self.column_step.1),
+            ..self
+        }
+    }
+
+    /// Mirrors the view top to bottom.
+    pub fn flip_vertical(self) -> Self {
+        Self {
+            origin: self.source_point(0, self.last_row()),
+            row_step: (-self.row_step.0, -self.row_step.1),
+            ..self
+        }
+    }
+
+    /// Restricts the view to the rectangle of `columns` x `rows` cells starting at (i, j).
+    /// The rectangle is clipped to the view.
+    pub fn window(self, i: usize, j: usize, columns: usize, rows: usize) -> Self {
+        let i = i.min(self.columns);
+        let j = j.min(self.rows);
+        Self {
+            origin: self.source_point(i as isize, j as isize),
+            columns: columns.min(self.columns - i),
+            rows: rows.min(self.rows - j),
+            ..self
+        }
+    }
+
+    /// Gets the dimensions of the view as (columns, rows).
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.columns, self.rows)
+    }
+
+    pub fn is_inside(&self, i: isize, j: isize) -> bool {
+        (i >= 0) && (i < self.columns as isize) && (j >= 0) && (j < self.rows as isize)
+    }
+
+    /// Reads a cell from the view at (i, j), where `i` is the column and `j` is the row.
+    pub fn get(&self, i: usize, j: usize) -> Option<&'a T> {
+        self.get_point((i as isize, j as isize))
+    }
+
+    /// Reads the cell at point `p` of the view, if it is inside the view.
+    pub fn get_point(&self, p: Point) -> Option<&'a T> {
+        if !self.is_inside(p.0, p.1) {
+            return None;
+        }
+        self.grid.get_point(self.source_point(p.0, p.1))
+    }
+
+    /// Iterates over the cells of row `j` of the view.
+    pub fn row(&self, j: usize) -> impl Iterator<Item = &'a T> + '_ {
+        (0..self.columns).filter_map(move |i| self.get(i, j))
+    }
+
+    /// Iterates over the cells of the view, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> + '_ {
+        (0..self.rows).flat_map(move |j| self.row(j))
+    }
+
+    /// Copies the cells of the view into a new Grid. Fails if the view has no rows or no
+    /// columns, which happens when a window is clipped away.
+    pub fn materialize(&self) -> Result<Grid<T>, GridError>
+    where
+        T: Clone,
+    {
+        Grid::new(
+            (0..self.rows)
+                .map(|j| self.row(j).cloned().collect())
+                .collect(),
+        )
+    }
+}
+
+#[test]
+fn test_views() {
+    const GRID: &str = "abc\n\
+                        def";
+
+    let grid = Grid::from_string(GRID).unwrap();
+    let cells = |view: GridView<'_, char>| view.materialize().unwrap().to_string();
+
+    assert_eq!(cells(grid.transpose()), "ad\nbe\ncf");
+    assert_eq!(cells(grid.rotate_cw()), "da\neb\nfc");
+    assert_eq!(cells(grid.rotate_ccw()), "cf\nbe\nad");
+    assert_eq!(cells(grid.rotate_180()), "fed\ncba");
+    assert_eq!(cells(grid.flip_horizontal()), "cba\nfed");
+    assert_eq!(cells(grid.flip_vertical()), "def\nabc");
+    assert_eq!(cells(grid.window(1, 0, 5, 1)), "bc");
+
+    let round_trip = grid.rotate_cw().rotate_cw().rotate_cw().rotate_cw();
+    assert_eq!(cells(round_trip), GRID);
+    assert_eq!(grid.rotate_cw().window(0, 1, 2, 2).get(1, 0), Some(&'b'));
+    assert_eq!(grid.transpose().get(2, 0), None);
+
+    assert_eq!(grid.window(3, 0, 2, 2).materialize(), Err(GridError::Empty));
+    assert_eq!(grid.window(0, 1, 2, 0).materialize(), Err(GridError::Empty));
+}